@@ -0,0 +1,165 @@
+// Expansion for `#[builder(const_fn)]`.
+//
+// The constructor, the setters and build() are all `const fn`, so that tables
+// of values can be built in constants and statics with the same API as at
+// runtime:
+//
+//     static SERVERS: [Server; 2] = [
+//         Server::builder().host("a.example").port(80).build(),
+//         Server::builder().host("b.example").build(),
+//     ];
+//
+// A const fn can only move values around, not call trait methods or drop
+// anything, so setters take and return the builder by value and build()
+// returns the built value directly. A required field that is not set makes
+// build() panic, which fails the compilation of a constant.
+//
+// Values that are never used, such as one replaced by calling a setter
+// again, are forgotten with mem::forget, so that fields may still have types
+// with destructors.
+
+use crate::{BuilderTarget, FieldInfo, StructAttrs};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::DeriveInput;
+
+pub(crate) fn expand(
+    input: &DeriveInput,
+    struct_attrs: &StructAttrs,
+    target: &BuilderTarget,
+    field_infos: &[FieldInfo],
+) -> TokenStream {
+    let name = &input.ident;
+    let builder_ident = &target.builder_ident;
+    let path = &target.path;
+    let constructor = &target.constructor;
+    let generics = &input.generics;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let stored: Vec<&FieldInfo> = field_infos.iter().filter(|info| !info.skip).collect();
+
+    let builder_fields = stored.iter().map(|info| {
+        let name = &info.name;
+        let ty = info.ty;
+        if info.optional {
+            quote! { #name: #ty, }
+        } else {
+            quote! { #name: ::core::option::Option<#ty>, }
+        }
+    });
+
+    let builder_init = stored.iter().map(|info| {
+        let name = &info.name;
+        quote! { #name: ::core::option::Option::None, }
+    });
+
+    let setters = stored.iter().filter(|info| info.setter).map(|info| {
+        let name = &info.name;
+        let vis = &info.setter_vis;
+        let ty = info.ty;
+        let (arg_ty, value) = match info.option_inner {
+            Some(inner) if info.strip_option => {
+                (inner, quote!(::core::option::Option::Some(#name)))
+            }
+            _ if info.optional => (ty, quote!(#name)),
+            _ => (ty, quote!(::core::option::Option::Some(#name))),
+        };
+        let docs = info.setter_docs();
+        quote! {
+            #docs
+            #vis const fn #name(mut self, #name: #arg_ty) -> Self {
+                ::core::mem::forget(::core::mem::replace(&mut self.#name, #value));
+                self
+            }
+        }
+    });
+
+    // A const fn can only drop what the compiler knows needs no destructor,
+    // which moving out of an Option field by pattern does not tell it. So
+    // build() moves each field out of the builder whole and forgets the
+    // emptied builder, and the unset fields are forgotten rather than
+    // dropped.
+    let take_fields = stored.iter().map(|info| {
+        let name = &info.name;
+        quote! {
+            let #name = ::core::mem::replace(&mut self.#name, ::core::option::Option::None);
+        }
+    });
+
+    let build_fields = field_infos.iter().map(|info| {
+        let name = &info.name;
+        let member = &info.member;
+        match &info.default {
+            Some(default) if info.skip => quote! { #member: #default, },
+            Some(default) => {
+                let some = if info.optional {
+                    quote!(#name)
+                } else {
+                    quote!(::core::option::Option::unwrap(#name))
+                };
+                quote! {
+                    #member: if ::core::option::Option::is_some(&#name) {
+                        #some
+                    } else {
+                        ::core::mem::forget(#name);
+                        #default
+                    },
+                }
+            }
+            None if info.optional => quote! { #member: #name, },
+            None => quote! {
+                #member: ::core::option::Option::expect(
+                    #name,
+                    ::core::concat!("Field ", stringify!(#name), " is not set"),
+                ),
+            },
+        }
+    });
+
+    let derives = struct_attrs.builder_derives(false);
+    let vis = struct_attrs.vis();
+    let build_fn = struct_attrs.build_fn_name();
+
+    let builder_docs = target.builder_docs(field_infos);
+    let constructor_docs = target.constructor_docs();
+    let build_doc = format!(
+        "Builds a [`{}`], in constant contexts as well as at runtime.\n\n\
+         # Panics\n\n\
+         Panics if a required field is not set, which fails the compilation when \
+         evaluating a constant.",
+        target.doc_path()
+    );
+
+    quote! {
+        #builder_docs
+        #derives
+        #vis struct #builder_ident #generics #where_clause {
+            #(#builder_fields)*
+            // Not every parameter of an enum is used by each of its variants.
+            __marker: ::core::marker::PhantomData<fn() -> #name #ty_generics>,
+        }
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            #constructor_docs
+            #vis const fn #constructor() -> #builder_ident #ty_generics {
+                #builder_ident {
+                    #(#builder_init)*
+                    __marker: ::core::marker::PhantomData,
+                }
+            }
+        }
+
+        impl #impl_generics #builder_ident #ty_generics #where_clause {
+            #(#setters)*
+
+            #[doc = #build_doc]
+            pub const fn #build_fn(mut self) -> #name #ty_generics {
+                #(#take_fields)*
+                ::core::mem::forget(self);
+                #path {
+                    #(#build_fields)*
+                }
+            }
+        }
+    }
+}
@@ -1,204 +1,2023 @@
 use proc_macro::TokenStream;
-use quote::quote;
+use quote::{format_ident, quote, quote_spanned};
+use syn::spanned::Spanned;
 use syn::{parse_macro_input, DeriveInput, PathArguments, Type};
 
+mod const_fn;
+mod typestate;
+
 #[proc_macro_derive(Builder, attributes(builder))]
 pub fn derive(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
-    let name = input.ident;
-    let builder_ident = syn::Ident::new(&format!("{}Builder", name), name.span());
 
-    let fields = match input.data {
+    TokenStream::from(expand(&input).unwrap_or_else(syn::Error::into_compile_error))
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    let mut errors = Errors::default();
+
+    // Keep checking the fields after a bad struct attribute so that their
+    // errors are reported in the same compile.
+    let struct_attrs = get_struct_attrs(&input.attrs).unwrap_or_else(|err| {
+        errors.push(err);
+        StructAttrs::default()
+    });
+
+    let mut targets = Vec::new();
+
+    match input.data {
         syn::Data::Struct(ref data) => match data.fields {
-            syn::Fields::Named(ref fields) => &fields.named,
-            _ => panic!("Only named fields are supported"),
+            syn::Fields::Named(syn::FieldsNamed {
+                named: ref fields, ..
+            })
+            | syn::Fields::Unnamed(syn::FieldsUnnamed {
+                unnamed: ref fields,
+                ..
+            }) => targets.push(BuilderTarget {
+                builder_ident: match &struct_attrs.name {
+                    Some(builder_ident) => builder_ident.clone(),
+                    None => syn::Ident::new(&format!("{}Builder", name), name.span()),
+                },
+                path: quote!(#name),
+                constructor: match &struct_attrs.constructor {
+                    Some(constructor) => constructor.clone(),
+                    None => syn::Ident::new("builder", name.span()),
+                },
+                fields,
+            }),
+            syn::Fields::Unit => errors.push(syn::Error::new_spanned(
+                name,
+                "`Builder` cannot be derived for a unit struct",
+            )),
         },
-        _ => panic!("Only structs are supported"),
+        // Every variant with fields gets a builder of its own, e.g.
+        // `Shape::circle_builder()` returning a `ShapeCircleBuilder`. Tuple
+        // variants are set like tuple structs, with `_0`, `_1`, ...
+        syn::Data::Enum(ref data) => {
+            if let Some(to_builder) = &struct_attrs.to_builder {
+                errors.push(syn::Error::new_spanned(
+                    to_builder,
+                    "`to_builder` is not supported on enums because each variant has its own builder",
+                ));
+            }
+            for renamed in [&struct_attrs.name, &struct_attrs.constructor]
+                .into_iter()
+                .flatten()
+            {
+                errors.push(syn::Error::new_spanned(
+                    renamed,
+                    "the builders of an enum's variants cannot be renamed",
+                ));
+            }
+            for variant in &data.variants {
+                if let syn::Fields::Named(syn::FieldsNamed {
+                    named: ref fields, ..
+                })
+                | syn::Fields::Unnamed(syn::FieldsUnnamed {
+                    unnamed: ref fields,
+                    ..
+                }) = variant.fields
+                {
+                    let variant_ident = &variant.ident;
+                    targets.push(BuilderTarget {
+                        builder_ident: syn::Ident::new(
+                            &format!("{}{}Builder", name, variant_ident),
+                            variant_ident.span(),
+                        ),
+                        path: quote!(#name::#variant_ident),
+                        constructor: syn::Ident::new(
+                            &format!("{}_builder", to_snake_case(&variant_ident.to_string())),
+                            variant_ident.span(),
+                        ),
+                        fields,
+                    });
+                }
+            }
+        }
+        syn::Data::Union(ref data) => errors.push(syn::Error::new_spanned(
+            data.union_token,
+            "`Builder` cannot be derived for a union",
+        )),
     };
 
-    struct FieldInfo<'a> {
-        name: syn::Ident,
-        ty: &'a Type,
-        each_attr: Option<syn::Ident>,
+    let mut target_fields = Vec::new();
+
+    for target in &targets {
+        let mut field_infos = Vec::new();
+
+        for (index, f) in target.fields.iter().enumerate() {
+            match get_field_info(f, index, &struct_attrs) {
+                Ok(info) => field_infos.push(info),
+                Err(err) => errors.push(err),
+            }
+        }
+        target_fields.push(field_infos);
     }
 
-    fn get_each_attr(field: &syn::Field) -> Result<Option<syn::Ident>, proc_macro2::TokenStream> {
-        for attr in &field.attrs {
-            if attr.path().is_ident("builder") {
-                let mut each_value = None;
+    errors.finish()?;
 
-                let result = attr.parse_nested_meta(|meta| {
-                    if meta.path.is_ident("each") {
-                        let value = meta.value()?;
-                        let string_value = value.parse::<syn::LitStr>()?;
-                        each_value =
-                            Some(syn::Ident::new(&string_value.value(), string_value.span()));
-                        Ok(())
-                    } else {
-                        Err(syn::Error::new_spanned(
-                            attr.meta.clone(),
-                            "expected `builder(each = \"...\")`",
-                        ))
-                    }
-                });
+    let error_ident = match &struct_attrs.name {
+        Some(builder_ident) => syn::Ident::new(&format!("{}Error", builder_ident), name.span()),
+        None => syn::Ident::new(&format!("{}BuilderError", name), name.span()),
+    };
+    // Neither typestate nor const builders can fail to build.
+    let mut expanded = if struct_attrs.typestate.is_some() || struct_attrs.const_fn.is_some() {
+        quote!()
+    } else {
+        expand_error(&error_ident, &struct_attrs)
+    };
 
-                return match result {
-                    Ok(_) => Ok(each_value),
-                    Err(err) => Err(err.to_compile_error()),
-                };
-            }
+    for (target, field_infos) in targets.iter().zip(&target_fields) {
+        expanded.extend(if struct_attrs.typestate.is_some() {
+            typestate::expand(input, &struct_attrs, target, field_infos)
+        } else if struct_attrs.const_fn.is_some() {
+            const_fn::expand(input, &struct_attrs, target, field_infos)
+        } else {
+            expand_builder(input, &struct_attrs, target, field_infos, &error_ident)
+        });
+    }
+
+    Ok(expanded)
+}
+
+// Collects errors so that all of them are reported in a single compile rather
+// than one per attempt.
+#[derive(Default)]
+struct Errors {
+    combined: Option<syn::Error>,
+}
+
+impl Errors {
+    fn push(&mut self, err: syn::Error) {
+        match &mut self.combined {
+            Some(combined) => combined.combine(err),
+            None => self.combined = Some(err),
         }
-        Ok(None)
     }
 
-    fn is_option(ty: &Type) -> bool {
-        if let Type::Path(type_path) = ty {
-            if let Some(segment) = type_path.path.segments.first() {
-                return segment.ident == "Option"
-                    && matches!(segment.arguments, PathArguments::AngleBracketed(_));
+    fn finish(self) -> syn::Result<()> {
+        match self.combined {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+}
+
+// One builder to generate: for the struct, or for one variant of the enum.
+struct BuilderTarget<'a> {
+    builder_ident: syn::Ident,
+    // The struct or enum variant that build() constructs.
+    path: proc_macro2::TokenStream,
+    // The associated function of the input type returning a new builder.
+    constructor: syn::Ident,
+    fields: &'a syn::punctuated::Punctuated<syn::Field, syn::Token![,]>,
+}
+
+impl BuilderTarget<'_> {
+    // The struct or enum variant as written in an intra-doc link.
+    fn doc_path(&self) -> String {
+        self.path.to_string().replace(' ', "")
+    }
+
+    // Rustdoc for the builder type, listing the fields that build() requires
+    // and the values of the others when they are not set.
+    fn builder_docs(&self, field_infos: &[FieldInfo]) -> proc_macro2::TokenStream {
+        let mut required = String::new();
+        let mut optional = String::new();
+        for info in field_infos.iter().filter(|info| info.setter) {
+            let name = syn::ext::IdentExt::unraw(&info.name);
+            if info.is_required() {
+                required.push_str(&format!("\n- `{}`", name));
+                continue;
             }
+            let default = match (&info.default_doc, &info.each) {
+                (Some(default), _) => format!("`{}`", default),
+                (None, Some(_)) => "empty".to_string(),
+                (None, None) if info.sub_builder.is_some() => {
+                    "built by its own builder".to_string()
+                }
+                (None, None) => "`None`".to_string(),
+            };
+            optional.push_str(&format!("\n- `{}`: {}", name, default));
+        }
+
+        let mut doc = format!("Builder for [`{}`].", self.doc_path());
+        if !required.is_empty() {
+            doc.push_str("\n\nRequired fields:\n");
+            doc.push_str(&required);
         }
-        false
+        if !optional.is_empty() {
+            doc.push_str("\n\nOptional fields, and their value when not set:\n");
+            doc.push_str(&optional);
+        }
+        quote!(#[doc = #doc])
     }
 
-    fn inner_type_of_vec(ty: &Type) -> &Type {
-        if let Type::Path(type_path) = ty {
-            if let Some(seg) = type_path.path.segments.first() {
-                if seg.ident == "Vec" {
-                    if let PathArguments::AngleBracketed(ref args) = seg.arguments {
-                        if let Some(syn::GenericArgument::Type(inner_ty)) = args.args.first() {
-                            return inner_ty;
-                        }
+    // Rustdoc for the associated function returning a new builder.
+    fn constructor_docs(&self) -> proc_macro2::TokenStream {
+        let doc = format!(
+            "Returns a builder for [`{}`] with no fields set.",
+            self.doc_path()
+        );
+        quote!(#[doc = #doc])
+    }
+}
+
+fn expand_error(error_ident: &syn::Ident, struct_attrs: &StructAttrs) -> proc_macro2::TokenStream {
+    let vis = struct_attrs.vis();
+    let alloc = struct_attrs.alloc();
+    // core::error::Error is the same trait, but only since Rust 1.81.
+    let error_trait = if struct_attrs.no_std {
+        quote!(::core::error::Error)
+    } else {
+        quote!(std::error::Error)
+    };
+
+    quote! {
+        /// The error returned by a builder that cannot build its value.
+        #[derive(Debug)]
+        #vis enum #error_ident {
+            /// The one required field of the builder's own that was not set
+            /// before calling `build()`.
+            UninitializedField(&'static str),
+            /// Required fields that were not set before calling `build()`,
+            /// when there are several. Fields of a `sub_builder` field are
+            /// always reported here, listed as e.g. `server.port`.
+            UninitializedFields(#alloc::vec::Vec<#alloc::string::String>),
+            /// The built value was rejected by the `build_fn(validate = "...")` function.
+            ValidationError(#alloc::string::String),
+        }
+
+        impl ::core::fmt::Display for #error_ident {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                match self {
+                    #error_ident::UninitializedField(field) => {
+                        write!(f, "Field {} is not set", field)
                     }
+                    #error_ident::UninitializedFields(fields) => {
+                        write!(f, "Fields {} are not set", fields.join(", "))
+                    }
+                    #error_ident::ValidationError(message) => f.write_str(message),
                 }
             }
         }
-        ty
-    }
 
-    fn inner_type_of_option(ty: &Type) -> &Type {
-        if let Type::Path(type_path) = ty {
-            if let Some(seg) = type_path.path.segments.first() {
-                if seg.ident == "Option" {
-                    if let PathArguments::AngleBracketed(ref args) = seg.arguments {
-                        if let Some(syn::GenericArgument::Type(inner_ty)) = args.args.first() {
-                            return inner_ty;
-                        }
+        impl #error_ident {
+            /// The names of all required fields that were not set, or none if
+            /// the error is not about missing fields.
+            pub fn missing_fields(&self) -> #alloc::vec::Vec<&str> {
+                match self {
+                    #error_ident::UninitializedField(field) => #alloc::vec![*field],
+                    #error_ident::UninitializedFields(fields) => {
+                        fields.iter().map(#alloc::string::String::as_str).collect()
                     }
+                    #error_ident::ValidationError(_) => #alloc::vec::Vec::new(),
                 }
             }
         }
-        ty
+
+        impl #error_trait for #error_ident {}
+
+        impl ::core::convert::From<#alloc::string::String> for #error_ident {
+            fn from(message: #alloc::string::String) -> Self {
+                #error_ident::ValidationError(message)
+            }
+        }
     }
+}
 
-    let mut field_infos = Vec::new();
+fn expand_builder(
+    input: &DeriveInput,
+    struct_attrs: &StructAttrs,
+    target: &BuilderTarget,
+    field_infos: &[FieldInfo],
+    error_ident: &syn::Ident,
+) -> proc_macro2::TokenStream {
+    let name = &input.ident;
+    let builder_ident = &target.builder_ident;
+    let path = &target.path;
+    let constructor = &target.constructor;
 
-    for f in fields {
-        let name = f.ident.clone().expect("Expected named field");
-        let ty = &f.ty;
+    let generics = &input.generics;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
-        let each_attr = match get_each_attr(f) {
-            Ok(attr) => attr,
-            Err(err) => return TokenStream::from(err),
-        };
+    let alloc = struct_attrs.alloc();
 
-        field_infos.push(FieldInfo {
-            name,
-            ty,
-            each_attr,
-        });
-    }
+    let build_error = match &struct_attrs.error {
+        Some(path) => quote!(#path),
+        None => quote!(#error_ident),
+    };
+
+    // Skipped fields have no place in the builder and are only assigned their
+    // default by build().
+    let stored: Vec<&FieldInfo> = field_infos.iter().filter(|info| !info.skip).collect();
 
-    let builder_fields = field_infos.iter().map(|info| {
+    let builder_fields = stored.iter().map(|info| {
         let name = &info.name;
-        if info.each_attr.is_some() {
-            let inner_ty = inner_type_of_vec(info.ty);
-            quote! { #name: std::vec::Vec<#inner_ty>, }
-        } else if is_option(info.ty) {
+        // Documents use the struct's field names even where the setter has
+        // been renamed.
+        let rename = match &info.member {
+            syn::Member::Named(member) if struct_attrs.serde.is_some() && *member != *name => {
+                let key = syn::ext::IdentExt::unraw(member).to_string();
+                quote!(#[serde(rename = #key)])
+            }
+            _ => quote!(),
+        };
+        if let Some(sub_builder) = &info.sub_builder {
+            quote! { #rename #name: #sub_builder, }
+        } else if info.stores_collection() || info.optional {
             let ty = info.ty;
-            quote! { #name: #ty, }
+            quote! { #rename #name: #ty, }
         } else {
             let ty = info.ty;
-            quote! { #name: std::option::Option<#ty>, }
+            quote! { #rename #name: ::core::option::Option<#ty>, }
         }
     });
 
-    let builder_init = field_infos.iter().map(|info| {
-        let name = &info.name;
-        if info.each_attr.is_some() {
-            quote! { #name: std::vec::Vec::new(), }
+    // The value of a field in a new builder, which clear_<field>() restores.
+    let initial_value = |info: &FieldInfo| {
+        if info.stores_collection() || info.sub_builder.is_some() {
+            quote!(::core::default::Default::default())
         } else {
-            quote! { #name: std::option::Option::None, }
+            quote!(::core::option::Option::None)
         }
+    };
+
+    let builder_init = stored.iter().map(|info| {
+        let name = &info.name;
+        let value = initial_value(info);
+        quote! { #name: #value, }
     });
 
-    let setters = field_infos.iter().map(|info| {
+    let pattern = struct_attrs.pattern.unwrap_or(Pattern::Mutable);
+
+    let setters = stored.iter().map(|info| field_setters(info, pattern));
+
+    // Let a partially filled builder be inspected and fields be unset again.
+    let accessors = stored.iter().map(|info| {
         let name = &info.name;
-        let ty = info.ty;
+        let vis = &info.setter_vis;
+        let unraw = syn::ext::IdentExt::unraw(name);
+        let getter = info.method_name("get");
+        let (getter_ty, getter_value) = if info.stores_collection() {
+            let ty = info.ty;
+            (quote!(&#ty), quote!(&self.#name))
+        } else if let Some(sub_builder) = &info.sub_builder {
+            (quote!(&#sub_builder), quote!(&self.#name))
+        } else if info.optional && info.option_inner.is_none() {
+            let ty = info.ty;
+            (quote!(&#ty), quote!(&self.#name))
+        } else {
+            let ty = info.option_inner.unwrap_or(info.ty);
+            (
+                quote!(::core::option::Option<&#ty>),
+                quote!(::core::option::Option::as_ref(&self.#name)),
+            )
+        };
+
+        let getter_doc = format!("Returns `{}` as set so far.", unraw);
+        let clear = info.method_name("clear");
+        let clear_doc = format!("Resets `{}` to its value in a new builder.", unraw);
+        let value = initial_value(info);
+        let clear = pattern.setter(
+            quote!(#[doc = #clear_doc]),
+            vis,
+            &clear,
+            quote!(),
+            |builder| {
+                quote! { #builder.#name = #value; }
+            },
+        );
 
-        if let Some(each_name) = &info.each_attr {
-            let inner_ty = inner_type_of_vec(ty);
+        quote! {
+            #[doc = #getter_doc]
+            #vis fn #getter(&self) -> #getter_ty {
+                #getter_value
+            }
+
+            #clear
+        }
+    });
+
+    // Fields set in `other` replace those in `self`; `each` collections are
+    // appended to unless declared with `builder(merge = "replace")`. A
+    // collection with a default only counts as set once it is added to.
+    let merge_fields = stored.iter().map(|info| {
+        let name = &info.name;
+        if info.sub_builder.is_some() {
+            quote! { self.#name.__merge(other.#name); }
+        } else if info.each.is_some() && info.default.is_some() && info.merge == Merge::Append {
             quote! {
-                pub fn #each_name(&mut self, #each_name: #inner_ty) -> &mut Self {
-                    self.#name.push(#each_name);
-                    self }
+                if let ::core::option::Option::Some(items) = other.#name {
+                    match self.#name {
+                        ::core::option::Option::Some(ref mut collection) => {
+                            ::core::iter::Extend::extend(collection, items);
+                        }
+                        ::core::option::Option::None => {
+                            self.#name = ::core::option::Option::Some(items);
+                        }
+                    }
+                }
             }
-        } else if is_option(ty) {
-            let inner_ty = inner_type_of_option(ty);
+        } else if info.stores_collection() && info.merge == Merge::Replace {
             quote! {
-                pub fn #name(&mut self, #name: #inner_ty) -> &mut Self {
-                    self.#name = std::option::Option::Some(#name);
-                    self
+                let mut items = ::core::iter::IntoIterator::into_iter(other.#name).peekable();
+                if items.peek().is_some() {
+                    self.#name = ::core::default::Default::default();
+                    ::core::iter::Extend::extend(&mut self.#name, items);
                 }
             }
+        } else if info.stores_collection() {
+            quote! { ::core::iter::Extend::extend(&mut self.#name, other.#name); }
         } else {
             quote! {
-                pub fn #name(&mut self, #name: #ty) -> &mut Self {
-                    self.#name = std::option::Option::Some(#name);
-                    self
+                if other.#name.is_some() {
+                    self.#name = other.#name;
                 }
             }
         }
     });
+    let merge = pattern.setter(
+        quote! {
+            /// Overrides the fields of this builder with those set in
+            /// `other`. Collections are appended to, unless declared with
+            /// `builder(merge = "replace")`.
+        },
+        &quote!(pub),
+        &syn::Ident::new("merge", proc_macro2::Span::call_site()),
+        quote!(other: Self),
+        |builder| quote! { #builder.__merge(other); },
+    );
 
-    let build_fields = field_infos.iter().map(|info| {
+    // Lists the unset required fields, including those of sub-builders, in
+    // field order.
+    let missing_checks: Vec<proc_macro2::TokenStream> = field_infos
+        .iter()
+        .filter_map(|info| {
+            let name = &info.name;
+            if info.sub_builder.is_some() {
+                Some(quote! {
+                    for field in self.#name.__missing_fields() {
+                        missing.push(#alloc::format!("{}.{}", stringify!(#name), field));
+                    }
+                })
+            } else if info.is_required() {
+                Some(quote! {
+                    if self.#name.is_none() {
+                        missing.push(#alloc::string::String::from(stringify!(#name)));
+                    }
+                })
+            } else {
+                None
+            }
+        })
+        .collect();
+    let missing_fields = if missing_checks.is_empty() {
+        quote!(#alloc::vec::Vec::new())
+    } else {
+        quote! {
+            let mut missing = #alloc::vec::Vec::new();
+            #(#missing_checks)*
+            missing
+        }
+    };
+
+    // Check every required field up front so that a single error lists all of
+    // the missing ones, and so that a failed build leaves the builder intact.
+    let check_required = if missing_checks.is_empty() {
+        quote!()
+    } else {
+        // A single missing field of this builder's own is reported by name.
+        let required = field_infos
+            .iter()
+            .filter(|info| info.is_required())
+            .map(|info| &info.name);
+        quote! {
+            let missing = self.__missing_fields();
+            if !missing.is_empty() {
+                let err = if missing.len() == 1 {
+                    #(
+                        if self.#required.is_none() {
+                            #error_ident::UninitializedField(stringify!(#required))
+                        } else
+                    )* {
+                        #error_ident::UninitializedFields(missing)
+                    }
+                } else {
+                    #error_ident::UninitializedFields(missing)
+                };
+                return ::core::result::Result::Err(::core::convert::From::from(err));
+            }
+        }
+    };
+
+    // A sub-builder can only fail its own validation, as missing nested
+    // fields have already been reported.
+    let sub_builder_error = |name: &syn::Ident| {
+        quote! {
+            #error_ident::ValidationError(#alloc::format!("{}: {}", stringify!(#name), err))
+        }
+    };
+
+    // A mutable builder builds its sub-builders in place before taking any
+    // other field, so that a sub-builder failing validation leaves the
+    // builder as it was. The sub-builders built before the failing one are
+    // restored from their built values.
+    let sub_builders: Vec<(&FieldInfo, &Type)> = stored
+        .iter()
+        .filter_map(|info| Some((*info, info.sub_builder.as_ref()?)))
+        .collect();
+    let build_sub_builders = sub_builders.iter().enumerate().map(|(index, (info, _))| {
+        if pattern != Pattern::Mutable {
+            return quote!();
+        }
+        let restore_built = sub_builders[..index].iter().map(|(built, sub_builder)| {
+            let name = &built.name;
+            let binding = built.binding();
+            quote! { self.#name = <#sub_builder>::__from_built(#binding); }
+        });
         let name = &info.name;
-        let ty = info.ty;
+        let binding = info.binding();
+        let error = sub_builder_error(name);
+        quote! {
+            let #binding = match self.#name.__build() {
+                ::core::result::Result::Ok(value) => value,
+                ::core::result::Result::Err(err) => {
+                    #(#restore_built)*
+                    return ::core::result::Result::Err(::core::convert::From::from(#error));
+                }
+            };
+        }
+    });
 
-        if info.each_attr.is_some() {
-            quote! { #name: self.#name.clone(), }
-        } else if is_option(ty) {
-            quote! { #name: self.#name.take(), }
+    let build_fields = field_infos.iter().map(|info| {
+        let member = &info.member;
+        let value = pattern.take_field(&info.name);
+
+        if let (true, Some(default)) = (info.skip, &info.default) {
+            quote! { #member: #default, }
+        } else if let Some(sub_builder) = &info.sub_builder {
+            let name = &info.name;
+            let built = match pattern {
+                Pattern::Mutable => {
+                    let binding = info.binding();
+                    return quote! { #member: #binding, };
+                }
+                Pattern::Owned => quote! {
+                    {
+                        let mut builder = self.#name;
+                        builder.__build()
+                    }
+                },
+                // Every setter of an immutable builder clones it,
+                // sub-builders included.
+                Pattern::Immutable => {
+                    let ty = info.ty;
+                    quote_spanned! {ty.span()=>
+                        {
+                            fn sub_builder_must_be_clone_with_immutable_pattern<
+                                T: ::core::clone::Clone,
+                            >() {}
+                            sub_builder_must_be_clone_with_immutable_pattern::<#sub_builder>();
+                            <#sub_builder as ::core::clone::Clone>::clone(&self.#name).__build()
+                        }
+                    }
+                }
+            };
+            let error = sub_builder_error(name);
+            quote! {
+                #member: ::core::result::Result::map_err(#built, |err| #error)?,
+            }
+        } else if info.stores_collection() {
+            quote! { #member: #value, }
+        } else if let Some(default) = &info.default {
+            if info.optional {
+                quote! { #member: #value.or_else(|| #default), }
+            } else {
+                quote! { #member: #value.unwrap_or_else(|| #default), }
+            }
+        } else if info.optional {
+            quote! { #member: #value, }
         } else {
+            quote! { #member: #value.unwrap(), }
+        }
+    });
+    let build_receiver = pattern.build_receiver();
+    let build_fn = struct_attrs.build_fn_name();
+    let build_by_ref = match pattern {
+        Pattern::Owned => quote!(::core::mem::take(self).#build_fn()),
+        Pattern::Mutable | Pattern::Immutable => quote!(self.#build_fn()),
+    };
+
+    // A mutable builder has given its fields to the built value by the time
+    // it is validated, and puts them back if validation fails so that the
+    // builder can be fixed and built again. Fields left to their default are
+    // unset again.
+    let defaulted: Vec<&syn::Ident> = stored
+        .iter()
+        .filter(|info| info.default.is_some())
+        .map(|info| &info.name)
+        .collect();
+    let restore_fields = stored.iter().map(|info| {
+        let name = &info.name;
+        let binding = info.binding();
+        if let Some(sub_builder) = &info.sub_builder {
+            quote! { self.#name = <#sub_builder>::__from_built(#binding); }
+        } else if info.stores_collection() {
+            quote! { self.#name = #binding; }
+        } else if let Some(index) = defaulted.iter().position(|defaulted| *defaulted == name) {
+            let value = if info.optional {
+                quote!(#binding)
+            } else {
+                quote!(::core::option::Option::Some(#binding))
+            };
             quote! {
-                #name: self.#name.take().ok_or_else(|| format!("Field {} is not set", stringify!(#name)))?,
+                self.#name = if unset[#index] {
+                    ::core::option::Option::None
+                } else {
+                    #value
+                };
             }
+        } else if info.optional {
+            quote! { self.#name = #binding; }
+        } else {
+            quote! { self.#name = ::core::option::Option::Some(#binding); }
         }
     });
+    let restore = pattern == Pattern::Mutable && struct_attrs.build_fn.validate.is_some();
+    let (record_unset, restore) = if restore {
+        let members = stored.iter().map(|info| &info.member);
+        let bindings = stored.iter().map(|info| info.binding());
+        let record_unset = if defaulted.is_empty() {
+            quote!()
+        } else {
+            quote! {
+                let unset = [#(self.#defaulted.is_none()),*];
+            }
+        };
+        (
+            record_unset,
+            quote! {
+                // The pattern of an enum variant is refutable, that of a
+                // struct is not.
+                #[allow(irrefutable_let_patterns)]
+                let #path { #(#members: #bindings,)* .. } = built else {
+                    ::core::unreachable!()
+                };
+                #(#restore_fields)*
+            },
+        )
+    } else {
+        (quote!(), quote!())
+    };
+
+    let validate = match &struct_attrs.build_fn.validate {
+        Some(validate) => quote! {
+            if let ::core::result::Result::Err(err) = #validate(&built) {
+                let err = #error_ident::ValidationError(#alloc::string::ToString::to_string(&err));
+                #restore
+                return ::core::result::Result::Err(::core::convert::From::from(err));
+            }
+        },
+        None => quote!(),
+    };
+
+    // Turns a built value back into a builder with all of its fields set, for
+    // to_builder() and to restore sub-builders. Only structs are built by
+    // sub-builders, and enums have no to_builder().
+    let from_built = if let syn::Data::Struct(_) = input.data {
+        let from_fields = stored.iter().map(|info| {
+            let name = &info.name;
+            let member = &info.member;
+            if let Some(sub_builder) = &info.sub_builder {
+                quote! { #name: <#sub_builder>::__from_built(value.#member), }
+            } else if info.stores_collection() || info.optional {
+                quote! { #name: value.#member, }
+            } else {
+                quote! { #name: ::core::option::Option::Some(value.#member), }
+            }
+        });
+        quote! {
+            #[doc(hidden)]
+            pub fn __from_built(value: #name #ty_generics) -> Self {
+                #builder_ident {
+                    #(#from_fields)*
+                    __marker: ::core::marker::PhantomData,
+                }
+            }
+        }
+    } else {
+        quote!()
+    };
+
+    let to_builder = if struct_attrs.to_builder.is_some() {
+        quote! {
+            impl #impl_generics #name #ty_generics #where_clause {
+                /// Returns a builder with every field set from this value.
+                pub fn to_builder(&self) -> #builder_ident #ty_generics
+                where
+                    Self: ::core::clone::Clone,
+                {
+                    ::core::convert::From::from(::core::clone::Clone::clone(self))
+                }
+            }
+
+            impl #impl_generics ::core::convert::From<#name #ty_generics> for #builder_ident #ty_generics #where_clause {
+                fn from(value: #name #ty_generics) -> Self {
+                    #builder_ident::__from_built(value)
+                }
+            }
+        }
+    } else {
+        quote!()
+    };
+
+    // Immutable setters return a modified copy of the builder.
+    let derives = struct_attrs.builder_derives(pattern == Pattern::Immutable);
+    let vis = struct_attrs.vis();
+
+    let builder_docs = target.builder_docs(field_infos);
+    let constructor_docs = target.constructor_docs();
+    let mut build_doc = format!(
+        "Builds a [`{}`] from the fields set so far.\n\n\
+         # Errors\n\n\
+         Fails with [`{}::UninitializedField`] or [`{}::UninitializedFields`] naming every \
+         required field that is not set",
+        target.doc_path(),
+        error_ident,
+        error_ident,
+    );
+    if struct_attrs.build_fn.validate.is_some()
+        || stored.iter().any(|info| info.sub_builder.is_some())
+    {
+        build_doc.push_str(&format!(
+            ", or with [`{}::ValidationError`] if the value is rejected by validation",
+            error_ident
+        ));
+    }
+    build_doc.push('.');
+    if struct_attrs.error.is_some() {
+        build_doc.push_str(" The error is converted into the builder's error type with `From`.");
+    }
+
+    // A partial document deserializes into a builder with the fields it lacks
+    // left unset, to be reported by build() like any other missing field.
+    let (derive_deserialize, skip_marker) = if struct_attrs.serde.is_some() {
+        (
+            quote! {
+                #[derive(::serde::Deserialize)]
+                #[serde(default)]
+            },
+            quote!(#[serde(skip)]),
+        )
+    } else {
+        (quote!(), quote!())
+    };
 
-    let expanded = quote! {
-        pub struct #builder_ident {
+    quote! {
+        #builder_docs
+        #derives
+        #derive_deserialize
+        #vis struct #builder_ident #generics #where_clause {
             #(#builder_fields)*
+            // Not every parameter of an enum is used by each of its variants.
+            #skip_marker
+            __marker: ::core::marker::PhantomData<fn() -> #name #ty_generics>,
+        }
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            #constructor_docs
+            #vis fn #constructor() -> #builder_ident #ty_generics {
+                ::core::default::Default::default()
+            }
         }
 
-        impl #name {
-            pub fn builder() -> #builder_ident {
+        // Lets a builder be taken out of a `sub_builder` field.
+        impl #impl_generics ::core::default::Default for #builder_ident #ty_generics #where_clause {
+            fn default() -> Self {
                 #builder_ident {
                     #(#builder_init)*
+                    __marker: ::core::marker::PhantomData,
                 }
             }
         }
 
-        impl #builder_ident {
+        impl #impl_generics #builder_ident #ty_generics #where_clause {
             #(#setters)*
 
-            pub fn build(&mut self) -> std::result::Result<#name, std::boxed::Box<dyn std::error::Error>> {
-                Ok(#name {
+            #(#accessors)*
+
+            #merge
+
+            #[doc(hidden)]
+            pub fn __merge(&mut self, other: Self) {
+                #(#merge_fields)*
+            }
+
+            /// Whether every required field, including those of
+            /// sub-builders, has been set. Validation is only run by the
+            /// build function.
+            pub fn is_complete(&self) -> bool {
+                self.__missing_fields().is_empty()
+            }
+
+            #[doc(hidden)]
+            pub fn __missing_fields(&self) -> #alloc::vec::Vec<#alloc::string::String> {
+                #missing_fields
+            }
+
+            // Builds the builder of a `sub_builder` field whatever its
+            // pattern and build function name.
+            #[doc(hidden)]
+            pub fn __build(&mut self) -> ::core::result::Result<#name #ty_generics, #build_error> {
+                #build_by_ref
+            }
+
+            #from_built
+
+            #[doc = #build_doc]
+            pub fn #build_fn(#build_receiver) -> ::core::result::Result<#name #ty_generics, #build_error> {
+                #check_required
+                #record_unset
+                #(#build_sub_builders)*
+                let built = #path {
                     #(#build_fields)*
-                })
+                };
+                #validate
+                ::core::result::Result::Ok(built)
+            }
+        }
+
+        #to_builder
+    }
+}
+
+// The setters of a field that the builder stores as an Option or collection.
+fn field_setters(info: &FieldInfo, pattern: Pattern) -> proc_macro2::TokenStream {
+    let name = &info.name;
+    let ty = info.ty;
+    let vis = &info.setter_vis;
+
+    if !info.setter {
+        return quote!();
+    }
+
+    // A sub-builder is configured in place whatever the builder's pattern.
+    if let Some(sub_builder) = &info.sub_builder {
+        let docs =
+            info.setter_docs_with("Returns the field's builder, to set its fields in place.");
+        return quote! {
+            #docs
+            #vis fn #name(&mut self) -> &mut #sub_builder {
+                &mut self.#name
+            }
+        };
+    }
+
+    if let Some(each) = &info.each {
+        // A collection with a default starts out as the default once it is
+        // added to.
+        let collection = |builder: &proc_macro2::TokenStream| match &info.default {
+            Some(default) => quote! {
+                ::core::option::Option::get_or_insert_with(&mut #builder.#name, || #default)
+            },
+            None => quote!(&mut #builder.#name),
+        };
+        let (params, item) = info.each_setter_arg(each);
+        let each_docs = info.setter_docs_with("Adds one item to the collection.");
+        let each_setter = pattern.setter(each_docs, vis, &each.setter, params, |builder| {
+            let collection = collection(builder);
+            quote! {
+                ::core::iter::Extend::extend(#collection, ::core::iter::once(#item));
             }
+        });
+
+        let item_ty = each.item.ty();
+        let extend_name = info.method_name("extend");
+        let extend_setter = pattern.setter(
+            info.setter_docs_with("Adds every item of `items` to the collection."),
+            vis,
+            &extend_name,
+            quote!(items: impl ::core::iter::IntoIterator<Item = #item_ty>),
+            |builder| {
+                let collection = collection(builder);
+                quote! { ::core::iter::Extend::extend(#collection, items); }
+            },
+        );
+
+        // The setter replacing the whole collection would clash with the
+        // one-item setter if they share a name.
+        let collection_setter = if each.setter != *name {
+            let (arg_ty, value) = info.setter_arg(name, ty);
+            let docs = info.setter_docs_with("Replaces the whole collection.");
+            let value = if info.stores_collection() {
+                value
+            } else {
+                quote!(::core::option::Option::Some(#value))
+            };
+            pattern.setter(docs, vis, name, quote!(#name: #arg_ty), |builder| {
+                quote! { #builder.#name = #value; }
+            })
+        } else {
+            quote!()
+        };
+
+        quote! {
+            #each_setter
+            #extend_setter
+            #collection_setter
         }
+    } else if info.optional && !(info.strip_option && info.option_inner.is_some()) {
+        let (arg_ty, value) = info.setter_arg(name, ty);
+        let setter = pattern.setter(
+            info.setter_docs(),
+            vis,
+            name,
+            quote!(#name: #arg_ty),
+            |builder| {
+                quote! { #builder.#name = #value; }
+            },
+        );
+        let try_setter = try_setter(info, ty, &pattern.build_receiver(), &pattern.output());
+        quote! {
+            #setter
+            #try_setter
+        }
+    } else {
+        let value_ty = info.option_inner.unwrap_or(ty);
+        let (arg_ty, value) = info.setter_arg(name, value_ty);
+        let setter = pattern.setter(
+            info.setter_docs(),
+            vis,
+            name,
+            quote!(#name: #arg_ty),
+            |builder| {
+                quote! { #builder.#name = ::core::option::Option::Some(#value); }
+            },
+        );
+        let try_setter = try_setter(info, value_ty, &pattern.build_receiver(), &pattern.output());
+        quote! {
+            #setter
+            #try_setter
+        }
+    }
+}
+
+// With `builder(try_setter)`, a `try_` setter accepting anything that
+// converts into the setter's `value_ty` with TryInto, returning the
+// conversion error instead of setting the field if it fails.
+fn try_setter(
+    info: &FieldInfo,
+    value_ty: &Type,
+    receiver: &proc_macro2::TokenStream,
+    output: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    if !info.try_setter {
+        return quote!();
+    }
+    let name = &info.name;
+    let vis = &info.setter_vis;
+    let try_name = info.method_name("try");
+    let docs = info.setter_docs_with(
+        "Converts `value` with `TryInto` first, returning the conversion error \
+         without setting the field if it fails.",
+    );
+    quote! {
+        #docs
+        #vis fn #try_name<__U: ::core::convert::TryInto<#value_ty>>(
+            #receiver,
+            value: __U,
+        ) -> ::core::result::Result<#output, <__U as ::core::convert::TryInto<#value_ty>>::Error> {
+            let value = ::core::convert::TryInto::try_into(value)?;
+            ::core::result::Result::Ok(self.#name(value))
+        }
+    }
+}
+
+#[derive(Default)]
+struct StructAttrs {
+    typestate: Option<syn::Path>,
+    error: Option<syn::Path>,
+    default: bool,
+    setter: SetterAttrs,
+    pattern: Option<Pattern>,
+    build_fn: BuildFnAttrs,
+    // Generate `to_builder()` and a From impl turning a value into a builder.
+    to_builder: Option<syn::Path>,
+    // Derive serde::Deserialize for the builder; needs the `serde` feature.
+    serde: Option<syn::Path>,
+    // The builder's name in place of `{Name}Builder`.
+    name: Option<syn::Ident>,
+    // The visibility of the builder, its constructor and its error type.
+    vis: Option<syn::Visibility>,
+    // The constructor's name in place of `builder`.
+    constructor: Option<syn::Ident>,
+    // Traits to derive for the builder, as in `derive(Debug, Clone)`.
+    derive: Vec<syn::Path>,
+    // Make the constructor, setters and build() `const fn`.
+    const_fn: Option<syn::Path>,
+    // Whether the generated code may only use `core` and `alloc`, as set by
+    // `crate_root = "core"` rather than the default `crate_root = "std"`.
+    no_std: bool,
+}
+
+impl StructAttrs {
+    fn vis(&self) -> proc_macro2::TokenStream {
+        match &self.vis {
+            Some(vis) => quote!(#vis),
+            None => quote!(pub),
+        }
+    }
+
+    // The crate providing Vec, String and format!. Everything else is taken
+    // from `::core`, which every crate can refer to.
+    fn alloc(&self) -> proc_macro2::TokenStream {
+        if self.no_std {
+            quote!(::alloc)
+        } else {
+            quote!(std)
+        }
+    }
+
+    fn build_fn_name(&self) -> syn::Ident {
+        match &self.build_fn.name {
+            Some(name) => name.clone(),
+            None => syn::Ident::new("build", proc_macro2::Span::call_site()),
+        }
+    }
+
+    // The derive attribute of the builder, which must implement Clone if its
+    // setters need to copy it.
+    fn builder_derives(&self, clone: bool) -> proc_macro2::TokenStream {
+        let mut derive = self.derive.clone();
+        if clone && !derive.iter().any(|path| path.is_ident("Clone")) {
+            derive.insert(0, syn::parse_quote!(Clone));
+        }
+        if derive.is_empty() {
+            quote!()
+        } else {
+            quote!(#[derive(#(#derive),*)])
+        }
+    }
+}
+
+// Options accepted inside `builder(build_fn(...))`.
+#[derive(Default)]
+struct BuildFnAttrs {
+    // Function called with a reference to the built value before build()
+    // returns it. An error it returns becomes a ValidationError.
+    validate: Option<syn::Path>,
+    // The name of the build function in place of `build`.
+    name: Option<syn::Ident>,
+}
+
+// How setters and build() receive the builder, from `builder(pattern = "...")`.
+#[derive(Clone, Copy, PartialEq)]
+enum Pattern {
+    // `&mut self -> &mut Self` setters; build() moves the values out.
+    Mutable,
+    // `self -> Self` setters; build() consumes the builder.
+    Owned,
+    // `&self -> Self` setters returning a copy; build() clones the values.
+    Immutable,
+}
+
+impl Pattern {
+    // A setter with the given attributes and parameters whose body assigns
+    // through the receiver expression passed to `body`.
+    fn setter(
+        self,
+        attrs: proc_macro2::TokenStream,
+        vis: &proc_macro2::TokenStream,
+        name: &syn::Ident,
+        params: proc_macro2::TokenStream,
+        body: impl FnOnce(&proc_macro2::TokenStream) -> proc_macro2::TokenStream,
+    ) -> proc_macro2::TokenStream {
+        match self {
+            Pattern::Mutable => {
+                let body = body(&quote!(self));
+                quote! {
+                    #attrs
+                    #vis fn #name(&mut self, #params) -> &mut Self {
+                        #body
+                        self
+                    }
+                }
+            }
+            Pattern::Owned => {
+                let body = body(&quote!(self));
+                quote! {
+                    #attrs
+                    #vis fn #name(mut self, #params) -> Self {
+                        #body
+                        self
+                    }
+                }
+            }
+            // The copy's name cannot clash with a parameter named after a
+            // field.
+            Pattern::Immutable => {
+                let body = body(&quote!(__builder));
+                quote! {
+                    #attrs
+                    #vis fn #name(&self, #params) -> Self {
+                        let mut __builder = ::core::clone::Clone::clone(self);
+                        #body
+                        __builder
+                    }
+                }
+            }
+        }
+    }
+
+    // The return type of a setter.
+    fn output(self) -> proc_macro2::TokenStream {
+        match self {
+            Pattern::Mutable => quote!(&mut Self),
+            Pattern::Owned | Pattern::Immutable => quote!(Self),
+        }
+    }
+
+    fn build_receiver(self) -> proc_macro2::TokenStream {
+        match self {
+            Pattern::Mutable => quote!(&mut self),
+            Pattern::Owned => quote!(self),
+            Pattern::Immutable => quote!(&self),
+        }
+    }
+
+    // The builder's stored value for a field, as used by build().
+    fn take_field(self, name: &syn::Ident) -> proc_macro2::TokenStream {
+        match self {
+            Pattern::Mutable => quote!(::core::mem::take(&mut self.#name)),
+            Pattern::Owned => quote!(self.#name),
+            Pattern::Immutable => quote!(::core::clone::Clone::clone(&self.#name)),
+        }
+    }
+}
+
+// Options accepted inside `builder(setter(...))`, on the struct or a field.
+// Unspecified options are inherited from the struct, then from the defaults.
+#[derive(Default)]
+struct SetterAttrs {
+    into: Option<bool>,
+    strip_option: Option<bool>,
+    // Only accepted on fields.
+    skip: Option<bool>,
+}
+
+struct FieldInfo<'a> {
+    // The field's name in the builder, which is also the name of its setter.
+    // Tuple struct fields are named `_0`, `_1`, ... unless renamed with
+    // `builder(name = "...")`.
+    name: syn::Ident,
+    // The field in the struct being built.
+    member: syn::Member,
+    ty: &'a Type,
+    each: Option<EachAttr>,
+    // Expression producing the field's value when its setter was not called.
+    default: Option<proc_macro2::TokenStream>,
+    // The default as written by the user, for the builder's documentation.
+    default_doc: Option<String>,
+    // Whether the setter is generic over `impl Into<T>`.
+    into: bool,
+    // Whether the setter of an Option<T> field takes T rather than Option<T>.
+    strip_option: bool,
+    // The builder of a `builder(sub_builder)` field's type, which the parent
+    // builder stores and builds in its own build().
+    sub_builder: Option<Type>,
+    // How merge() combines an `each` collection with the other builder's.
+    merge: Merge,
+    // Whether the field is left out of the builder altogether, always being
+    // built from its default.
+    skip: bool,
+    // Whether the field has a setter, which is not the case with `skip` or
+    // `setter(skip)`.
+    setter: bool,
+    // The visibility of the field's setters.
+    setter_vis: proc_macro2::TokenStream,
+    // Whether the field also gets a `try_` setter taking a TryInto argument.
+    try_setter: bool,
+    // Whether the field is an Option, which the builder stores as is and
+    // build() leaves None when unset.
+    optional: bool,
+    // The `T` of an optional field's `Option<T>`, which its setter takes
+    // unless `setter(strip_option = false)`. None when the field's type is an
+    // alias marked `builder(optional)`.
+    option_inner: Option<&'a Type>,
+    // The field's doc comments, which are copied onto its setters.
+    docs: Vec<&'a syn::Attribute>,
+}
+
+impl FieldInfo<'_> {
+    // Whether build() must fail when the setter was not called.
+    fn is_required(&self) -> bool {
+        self.each.is_none()
+            && self.default.is_none()
+            && self.sub_builder.is_none()
+            && !self.optional
+    }
+
+    // Whether the builder stores an `each` field's collection as is. One with
+    // a default is stored as an Option like other defaulted fields, so that
+    // merge() can tell whether it was set.
+    fn stores_collection(&self) -> bool {
+        self.each.is_some() && self.default.is_none()
+    }
+
+    // The name of a method of the builder operating on the field, e.g.
+    // `get_port`. The underscore of a tuple struct field's `_0` is dropped to
+    // give `get_0` rather than the non-snake-case `get__0`.
+    fn method_name(&self, prefix: &str) -> syn::Ident {
+        let name = syn::ext::IdentExt::unraw(&self.name).to_string();
+        syn::Ident::new(
+            &format!("{}_{}", prefix, name.trim_start_matches('_')),
+            self.name.span(),
+        )
+    }
+
+    // The local variable holding the field's value while build() takes the
+    // builder apart, e.g. `__port`. The prefix keeps it from shadowing
+    // build()'s own variables, whatever the fields are called.
+    fn binding(&self) -> syn::Ident {
+        format_ident!("__{}", syn::ext::IdentExt::unraw(&self.name))
+    }
+
+    // The field's doc comments for its setter, or a line naming the field if
+    // it has none.
+    fn setter_docs(&self) -> proc_macro2::TokenStream {
+        if self.docs.is_empty() {
+            let doc = format!("Sets `{}`.", syn::ext::IdentExt::unraw(&self.name));
+            quote!(#[doc = #doc])
+        } else {
+            let docs = &self.docs;
+            quote!(#(#docs)*)
+        }
+    }
+
+    // The setter docs followed by a paragraph telling apart one of several
+    // setters of the field.
+    fn setter_docs_with(&self, note: &str) -> proc_macro2::TokenStream {
+        let docs = self.setter_docs();
+        quote! {
+            #docs
+            #[doc = ""]
+            #[doc = #note]
+        }
+    }
+
+    // The parameter type of a setter accepting a `ty`, and the expression
+    // turning the parameter named `arg` into that `ty`.
+    fn setter_arg(
+        &self,
+        arg: &syn::Ident,
+        ty: &Type,
+    ) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+        if self.into {
+            (
+                quote!(impl ::core::convert::Into<#ty>),
+                quote!(::core::convert::Into::into(#arg)),
+            )
+        } else {
+            (quote!(#ty), quote!(#arg))
+        }
+    }
+
+    // The parameters of an `each` setter and the expression for the item it
+    // adds to the collection. Map entries are passed as a key and a value.
+    fn each_setter_arg(
+        &self,
+        each: &EachAttr,
+    ) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+        match &each.item {
+            EachItem::Value(ty) => {
+                let arg = &each.setter;
+                let (arg_ty, value) = self.setter_arg(arg, ty);
+                (quote!(#arg: #arg_ty), value)
+            }
+            EachItem::Entry(key_ty, value_ty) => {
+                let key = syn::Ident::new("key", each.setter.span());
+                let value = syn::Ident::new("value", each.setter.span());
+                let (key_arg_ty, key_expr) = self.setter_arg(&key, key_ty);
+                let (value_arg_ty, value_expr) = self.setter_arg(&value, value_ty);
+                (
+                    quote!(#key: #key_arg_ty, #value: #value_arg_ty),
+                    quote!((#key_expr, #value_expr)),
+                )
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Merge {
+    Append,
+    Replace,
+}
+
+// A field with `builder(each = "...")`: the name of the setter adding one item
+// at a time, and the type of those items.
+struct EachAttr {
+    setter: syn::Ident,
+    item: EachItem,
+}
+
+enum EachItem {
+    // Vec<T>, HashSet<T> and other collections extended with T.
+    Value(Box<Type>),
+    // HashMap<K, V> and BTreeMap<K, V>, extended with (K, V).
+    Entry(Box<Type>, Box<Type>),
+}
+
+impl EachItem {
+    fn ty(&self) -> proc_macro2::TokenStream {
+        match self {
+            EachItem::Value(ty) => quote!(#ty),
+            EachItem::Entry(key_ty, value_ty) => quote!((#key_ty, #value_ty)),
+        }
+    }
+}
+
+fn get_struct_attrs(attrs: &[syn::Attribute]) -> syn::Result<StructAttrs> {
+    let mut struct_attrs = StructAttrs::default();
+    let mut errors = Errors::default();
+
+    for attr in attrs {
+        if attr.path().is_ident("builder") {
+            let mut parse_option = |meta: &syn::meta::ParseNestedMeta| -> syn::Result<()> {
+                if meta.path.is_ident("typestate") {
+                    struct_attrs.typestate = Some(meta.path.clone());
+                    Ok(())
+                } else if meta.path.is_ident("const_fn") {
+                    struct_attrs.const_fn = Some(meta.path.clone());
+                    Ok(())
+                } else if meta.path.is_ident("to_builder") {
+                    struct_attrs.to_builder = Some(meta.path.clone());
+                    Ok(())
+                } else if meta.path.is_ident("serde") {
+                    if !cfg!(feature = "serde") {
+                        return Err(meta.error(
+                            "`builder(serde)` requires the `serde` feature of derive_builder",
+                        ));
+                    }
+                    struct_attrs.serde = Some(meta.path.clone());
+                    Ok(())
+                } else if meta.path.is_ident("name") {
+                    let value = meta.value()?;
+                    let string_value = value.parse::<syn::LitStr>()?;
+                    struct_attrs.name = Some(string_value.parse::<syn::Ident>()?);
+                    Ok(())
+                } else if meta.path.is_ident("vis") {
+                    let value = meta.value()?;
+                    let string_value = value.parse::<syn::LitStr>()?;
+                    struct_attrs.vis = Some(string_value.parse::<syn::Visibility>()?);
+                    Ok(())
+                } else if meta.path.is_ident("constructor") {
+                    let value = meta.value()?;
+                    let string_value = value.parse::<syn::LitStr>()?;
+                    struct_attrs.constructor = Some(string_value.parse::<syn::Ident>()?);
+                    Ok(())
+                } else if meta.path.is_ident("crate_root") {
+                    let value = meta.value()?;
+                    let string_value = value.parse::<syn::LitStr>()?;
+                    struct_attrs.no_std = match string_value.value().as_str() {
+                        "std" => false,
+                        "core" => true,
+                        _ => {
+                            return Err(syn::Error::new_spanned(
+                                string_value,
+                                "expected `\"std\"` or `\"core\"`",
+                            ))
+                        }
+                    };
+                    Ok(())
+                } else if meta.path.is_ident("derive") {
+                    meta.parse_nested_meta(|meta| {
+                        struct_attrs.derive.push(meta.path.clone());
+                        Ok(())
+                    })
+                } else if meta.path.is_ident("error") {
+                    let value = meta.value()?;
+                    let string_value = value.parse::<syn::LitStr>()?;
+                    struct_attrs.error = Some(string_value.parse::<syn::Path>()?);
+                    Ok(())
+                } else if meta.path.is_ident("default") {
+                    struct_attrs.default = true;
+                    Ok(())
+                } else if meta.path.is_ident("setter") {
+                    parse_setter_attrs(meta, &mut struct_attrs.setter)?;
+                    if struct_attrs.setter.skip.is_some() {
+                        return Err(meta.error("`setter(skip)` only applies to fields"));
+                    }
+                    Ok(())
+                } else if meta.path.is_ident("pattern") {
+                    let value = meta.value()?;
+                    let string_value = value.parse::<syn::LitStr>()?;
+                    struct_attrs.pattern = Some(match string_value.value().as_str() {
+                        "mutable" => Pattern::Mutable,
+                        "owned" => Pattern::Owned,
+                        "immutable" => Pattern::Immutable,
+                        _ => {
+                            return Err(syn::Error::new_spanned(
+                                string_value,
+                                "expected `\"mutable\"`, `\"owned\"` or `\"immutable\"`",
+                            ))
+                        }
+                    });
+                    Ok(())
+                } else if meta.path.is_ident("build_fn") {
+                    meta.parse_nested_meta(|meta| {
+                        if meta.path.is_ident("validate") {
+                            let value = meta.value()?;
+                            let string_value = value.parse::<syn::LitStr>()?;
+                            struct_attrs.build_fn.validate =
+                                Some(string_value.parse::<syn::Path>()?);
+                            Ok(())
+                        } else if meta.path.is_ident("name") {
+                            let value = meta.value()?;
+                            let string_value = value.parse::<syn::LitStr>()?;
+                            struct_attrs.build_fn.name = Some(string_value.parse::<syn::Ident>()?);
+                            Ok(())
+                        } else {
+                            Err(meta.error("expected `validate` or `name`"))
+                        }
+                    })
+                } else {
+                    Err(meta.error("unrecognized builder option"))
+                }
+            };
+            let result = attr.parse_nested_meta(|meta| {
+                if let Err(err) = parse_option(&meta) {
+                    errors.push(err);
+                    skip_option(meta.input)?;
+                }
+                Ok(())
+            });
+
+            if let Err(err) = result {
+                errors.push(err);
+            }
+        }
+    }
+
+    if let (Some(typestate), Some(_)) = (&struct_attrs.typestate, &struct_attrs.error) {
+        errors.push(syn::Error::new_spanned(
+            typestate,
+            "a typestate builder cannot fail, so it does not take `builder(error = \"...\")`",
+        ));
+    }
+    if let (Some(typestate), Some(_)) = (&struct_attrs.typestate, &struct_attrs.build_fn.validate) {
+        errors.push(syn::Error::new_spanned(
+            typestate,
+            "a typestate builder cannot fail, so it does not take `builder(build_fn(validate = \"...\"))`",
+        ));
+    }
+    if struct_attrs.typestate.is_none() {
+        if let Some(default) = struct_attrs
+            .derive
+            .iter()
+            .find(|path| path.is_ident("Default"))
+        {
+            errors.push(syn::Error::new_spanned(
+                default,
+                "the builder implements `Default` already",
+            ));
+        }
+    }
+    if let (Some(typestate), Some(_)) = (&struct_attrs.typestate, &struct_attrs.serde) {
+        errors.push(syn::Error::new_spanned(
+            typestate,
+            "a typestate builder cannot be deserialized, so it does not take `builder(serde)`",
+        ));
+    }
+    if let (Some(typestate), Some(pattern)) = (&struct_attrs.typestate, struct_attrs.pattern) {
+        if pattern != Pattern::Owned {
+            errors.push(syn::Error::new_spanned(
+                typestate,
+                "a typestate builder always uses `builder(pattern = \"owned\")`",
+            ));
+        }
+    }
+
+    // The const builder only has setters and a build() that cannot fail.
+    if let Some(const_fn) = &struct_attrs.const_fn {
+        let unsupported = [
+            (struct_attrs.typestate.is_some(), "typestate"),
+            (struct_attrs.error.is_some(), "error"),
+            (
+                struct_attrs.build_fn.validate.is_some(),
+                "build_fn(validate)",
+            ),
+            (struct_attrs.default, "default"),
+            (struct_attrs.to_builder.is_some(), "to_builder"),
+            (struct_attrs.serde.is_some(), "serde"),
+            (
+                matches!(struct_attrs.pattern, Some(pattern) if pattern != Pattern::Owned),
+                "pattern",
+            ),
+        ];
+        for (_, option) in unsupported.iter().filter(|(used, _)| *used) {
+            errors.push(syn::Error::new_spanned(
+                const_fn,
+                format!("`const_fn` cannot be combined with `{}`", option),
+            ));
+        }
+    }
+
+    errors.finish()?;
+    Ok(struct_attrs)
+}
+
+fn get_field_info<'a>(
+    field: &'a syn::Field,
+    index: usize,
+    struct_attrs: &StructAttrs,
+) -> syn::Result<FieldInfo<'a>> {
+    let member = match &field.ident {
+        Some(ident) => syn::Member::Named(ident.clone()),
+        None => syn::Member::Unnamed(syn::Index::from(index)),
+    };
+    let mut name = match &field.ident {
+        Some(ident) => ident.clone(),
+        None => syn::Ident::new(&format!("_{}", index), field.ty.span()),
+    };
+    let ty = &field.ty;
+    let mut each_setter = None;
+    let mut each_item = None;
+    let mut default = None;
+    let mut default_doc = None;
+    let mut setter = SetterAttrs::default();
+    let mut sub_builder = None;
+    let mut sub_builder_ty = None;
+    let mut merge = None;
+    let mut merge_path = None;
+    let mut skip = None;
+    let mut skip_default = None;
+    let mut setter_vis = None;
+    let mut try_setter = None;
+    let mut optional = None;
+    let mut required = None;
+    // Whether the field's default is Default::default().
+    let mut default_trait = false;
+    let mut errors = Errors::default();
+
+    for attr in &field.attrs {
+        if attr.path().is_ident("builder") {
+            let mut parse_option = |meta: &syn::meta::ParseNestedMeta| -> syn::Result<()> {
+                if meta.path.is_ident("each") {
+                    if meta.input.peek(syn::Token![=]) {
+                        let value = meta.value()?;
+                        let string_value = value.parse::<syn::LitStr>()?;
+                        each_setter = Some(string_value.parse::<syn::Ident>()?);
+                        return Ok(());
+                    }
+                    meta.parse_nested_meta(|meta| {
+                        let value = meta.value()?;
+                        let string_value = value.parse::<syn::LitStr>()?;
+                        if meta.path.is_ident("name") {
+                            each_setter = Some(string_value.parse::<syn::Ident>()?);
+                            Ok(())
+                        } else if meta.path.is_ident("item") {
+                            each_item = Some(string_value.parse::<Type>()?);
+                            Ok(())
+                        } else {
+                            Err(meta.error("expected `name` or `item`"))
+                        }
+                    })?;
+                    if each_setter.is_none() {
+                        return Err(meta.error("expected `each(name = \"...\")`"));
+                    }
+                    Ok(())
+                } else if meta.path.is_ident("default") {
+                    default = Some(if meta.input.peek(syn::Token![=]) {
+                        let value = meta.value()?;
+                        let string_value = value.parse::<syn::LitStr>()?;
+                        let expr = string_value.parse::<syn::Expr>()?;
+                        default_doc = Some(string_value.value());
+                        quote!(#expr)
+                    } else {
+                        default_trait = true;
+                        default_doc = Some("Default::default()".to_string());
+                        quote!(::core::default::Default::default())
+                    });
+                    Ok(())
+                } else if meta.path.is_ident("setter") {
+                    parse_setter_attrs(meta, &mut setter)
+                } else if meta.path.is_ident("merge") {
+                    let value = meta.value()?;
+                    let string_value = value.parse::<syn::LitStr>()?;
+                    merge = Some(match string_value.value().as_str() {
+                        "append" => Merge::Append,
+                        "replace" => Merge::Replace,
+                        _ => {
+                            return Err(syn::Error::new_spanned(
+                                string_value,
+                                "expected `append` or `replace`",
+                            ))
+                        }
+                    });
+                    merge_path = Some(meta.path.clone());
+                    Ok(())
+                } else if meta.path.is_ident("sub_builder") {
+                    if meta.input.peek(syn::Token![=]) {
+                        let value = meta.value()?;
+                        let string_value = value.parse::<syn::LitStr>()?;
+                        sub_builder_ty = Some(string_value.parse::<Type>()?);
+                    }
+                    sub_builder = Some(meta.path.clone());
+                    Ok(())
+                } else if meta.path.is_ident("skip") {
+                    if meta.input.peek(syn::Token![=]) {
+                        let value = meta.value()?;
+                        let string_value = value.parse::<syn::LitStr>()?;
+                        let expr = string_value.parse::<syn::Expr>()?;
+                        skip_default = Some((quote!(#expr), string_value.value()));
+                    }
+                    skip = Some(meta.path.clone());
+                    Ok(())
+                } else if meta.path.is_ident("vis") {
+                    let value = meta.value()?;
+                    let string_value = value.parse::<syn::LitStr>()?;
+                    setter_vis = Some(string_value.parse::<syn::Visibility>()?);
+                    Ok(())
+                } else if meta.path.is_ident("try_setter") {
+                    try_setter = Some(meta.path.clone());
+                    Ok(())
+                } else if meta.path.is_ident("optional") {
+                    optional = Some(meta.path.clone());
+                    Ok(())
+                } else if meta.path.is_ident("required") {
+                    required = Some(meta.path.clone());
+                    Ok(())
+                } else if meta.path.is_ident("name") {
+                    let value = meta.value()?;
+                    let string_value = value.parse::<syn::LitStr>()?;
+                    name = string_value.parse::<syn::Ident>()?;
+                    Ok(())
+                } else {
+                    Err(syn::Error::new_spanned(
+                        attr.meta.clone(),
+                        "expected `builder(each = \"...\")`",
+                    ))
+                }
+            };
+            let result = attr.parse_nested_meta(|meta| {
+                if let Err(err) = parse_option(&meta) {
+                    errors.push(err);
+                    skip_option(meta.input)?;
+                }
+                Ok(())
+            });
+
+            if let Err(err) = result {
+                errors.push(err);
+            }
+        }
+    }
+
+    // Only the collections recognized by their type are known to be
+    // iterable, which merge() needs to combine them.
+    if let (Some(item), None) = (&each_item, &merge) {
+        errors.push(syn::Error::new_spanned(
+            item,
+            "a collection with an explicit `item` type may not be iterable; \
+             specify how merge() combines it with `builder(merge = \"append\")` \
+             or `builder(merge = \"replace\")`",
+        ));
+    }
+    let each = match each_setter {
+        Some(setter) => {
+            let item = match each_item {
+                Some(item) => Some(EachItem::Value(Box::new(item))),
+                None => collection_item(ty),
+            };
+            match item {
+                Some(item) => Some(EachAttr { setter, item }),
+                None => {
+                    errors.push(syn::Error::new_spanned(
+                        ty,
+                        "cannot determine the item type of this collection; \
+                         specify it with `builder(each(name = \"...\", item = \"...\"))`",
+                    ));
+                    None
+                }
+            }
+        }
+        None => None,
     };
 
-    TokenStream::from(expanded)
+    if let (Some(path), None) = (&merge_path, &each) {
+        errors.push(syn::Error::new_spanned(
+            path,
+            "`merge` only applies to fields with `builder(each = \"...\")`",
+        ));
+    }
+
+    if let Some(path) = &skip {
+        if each.is_some() || sub_builder.is_some() {
+            errors.push(syn::Error::new_spanned(
+                path,
+                "`skip` cannot be combined with `each` or `sub_builder`",
+            ));
+        }
+        if skip_default.is_some() && default.is_some() {
+            errors.push(syn::Error::new_spanned(
+                path,
+                "`skip = \"...\"` cannot be combined with `default`",
+            ));
+        }
+    }
+
+    // Whether a type is std's Option can only be guessed from its path, so
+    // aliases and lookalikes have to be marked.
+    let (optional, option_inner) = match (&optional, &required, option_type(ty)) {
+        (Some(path), Some(_), _) => {
+            errors.push(syn::Error::new_spanned(
+                path,
+                "a field cannot be both `optional` and `required`",
+            ));
+            (false, None)
+        }
+        (Some(_), None, OptionType::Option(inner) | OptionType::Ambiguous(inner)) => {
+            (true, Some(inner))
+        }
+        (Some(_), None, OptionType::NotOption) => (true, None),
+        (None, Some(_), _) => (false, None),
+        (None, None, OptionType::Option(inner)) => (true, Some(inner)),
+        (None, None, OptionType::Ambiguous(_)) => {
+            errors.push(syn::Error::new_spanned(
+                ty,
+                "this may or may not be `std::option::Option`; mark the field \
+                 `#[builder(optional)]` if it is, or `#[builder(required)]` if it is not",
+            ));
+            (false, None)
+        }
+        (None, None, OptionType::NotOption) => (false, None),
+    };
+
+    if let Some(path) = &try_setter {
+        if each.is_some() || sub_builder.is_some() || skip.is_some() || setter.skip == Some(true) {
+            errors.push(syn::Error::new_spanned(
+                path,
+                "`try_setter` only applies to fields set with a single value",
+            ));
+        }
+    }
+
+    let sub_builder = match sub_builder {
+        Some(path) => {
+            if struct_attrs.typestate.is_some() {
+                errors.push(syn::Error::new_spanned(
+                    &path,
+                    "`sub_builder` cannot be combined with `typestate`",
+                ));
+            }
+            if each.is_some() || default.is_some() {
+                errors.push(syn::Error::new_spanned(
+                    &path,
+                    "`sub_builder` cannot be combined with `each` or `default`",
+                ));
+            }
+            // A builder renamed with `builder(name = "...")` has to be named
+            // here as well.
+            let builder_ty = sub_builder_ty.or_else(|| sub_builder_type(ty));
+            if builder_ty.is_none() {
+                errors.push(syn::Error::new_spanned(
+                    ty,
+                    "`sub_builder` requires a struct type that derives `Builder`, \
+                     or the type of its builder given as `sub_builder = \"...\"`",
+                ));
+            }
+            builder_ty
+        }
+        None => None,
+    };
+
+    errors.finish()?;
+
+    // A struct-level `default` only matters for fields that would otherwise
+    // be required; Option and `each` fields are already empty when unset.
+    if struct_attrs.default
+        && default.is_none()
+        && each.is_none()
+        && sub_builder.is_none()
+        && !optional
+    {
+        default = Some(quote!(::core::default::Default::default()));
+        default_doc = Some("Default::default()".to_string());
+    }
+
+    // A field without a setter can only ever have its default, unless it is
+    // still stored in the builder and set through merge() or to_builder().
+    let has_setter = !setter.skip.unwrap_or(false) && skip.is_none();
+    if let Some((skip_default, skip_default_doc)) = skip_default {
+        default = Some(skip_default);
+        default_doc = Some(skip_default_doc);
+        default_trait = false;
+    }
+    if !has_setter && default.is_none() && each.is_none() && sub_builder.is_none() {
+        default_trait = true;
+        default = Some(quote!(::core::default::Default::default()));
+        default_doc = Some("Default::default()".to_string());
+    }
+
+    let into = setter.into.or(struct_attrs.setter.into).unwrap_or(false);
+    let strip_option = setter
+        .strip_option
+        .or(struct_attrs.setter.strip_option)
+        .unwrap_or(true);
+
+    // A const fn cannot call trait methods such as Default::default() or
+    // Into::into(), nor build collections.
+    if struct_attrs.const_fn.is_some() {
+        if each.is_some() || sub_builder.is_some() || into || try_setter.is_some() {
+            return Err(syn::Error::new_spanned(
+                &field.ty,
+                "a `const_fn` builder cannot use `each`, `sub_builder`, `setter(into)` or \
+                 `try_setter`",
+            ));
+        }
+        if default_trait {
+            return Err(syn::Error::new_spanned(
+                &field.ty,
+                "a `const_fn` builder cannot call `Default::default()`; \
+                 give the field a const `default = \"...\"` or `skip = \"...\"` expression",
+            ));
+        }
+    }
+
+    Ok(FieldInfo {
+        name,
+        member,
+        ty,
+        each,
+        default,
+        default_doc,
+        into,
+        strip_option,
+        sub_builder,
+        merge: merge.unwrap_or(Merge::Append),
+        skip: skip.is_some(),
+        setter: has_setter,
+        setter_vis: match setter_vis {
+            Some(vis) => quote!(#vis),
+            None => quote!(pub),
+        },
+        try_setter: try_setter.is_some(),
+        optional,
+        option_inner,
+        docs: field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("doc"))
+            .collect(),
+    })
+}
+
+// Skips what is left of an option that failed to parse, so that the options
+// after it in the same attribute are still checked.
+fn skip_option(input: syn::parse::ParseStream) -> syn::Result<()> {
+    while !input.is_empty() && !input.peek(syn::Token![,]) {
+        input.parse::<proc_macro2::TokenTree>()?;
+    }
+    Ok(())
+}
+
+fn parse_setter_attrs(
+    meta: &syn::meta::ParseNestedMeta,
+    setter: &mut SetterAttrs,
+) -> syn::Result<()> {
+    meta.parse_nested_meta(|meta| {
+        if meta.path.is_ident("into") {
+            setter.into = Some(parse_flag(&meta)?);
+            Ok(())
+        } else if meta.path.is_ident("strip_option") {
+            setter.strip_option = Some(parse_flag(&meta)?);
+            Ok(())
+        } else if meta.path.is_ident("skip") {
+            setter.skip = Some(parse_flag(&meta)?);
+            Ok(())
+        } else {
+            Err(meta.error("expected `into`, `strip_option` or `skip`"))
+        }
+    })
+}
+
+// A boolean option written either as `name` or as `name = true|false`.
+fn parse_flag(meta: &syn::meta::ParseNestedMeta) -> syn::Result<bool> {
+    if meta.input.peek(syn::Token![=]) {
+        Ok(meta.value()?.parse::<syn::LitBool>()?.value)
+    } else {
+        Ok(true)
+    }
+}
+
+// `RoundedRect` becomes `rounded_rect` and `HTTPGet` becomes `http_get`: a
+// word starts at a capital that follows a lowercase letter or a digit, or at
+// the last capital of an acronym when a lowercase letter follows it.
+fn to_snake_case(ident: &str) -> String {
+    let chars: Vec<char> = ident.chars().collect();
+    let mut snake = String::new();
+    for (i, &ch) in chars.iter().enumerate() {
+        if ch.is_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let acronym_end = prev.is_uppercase()
+                && matches!(chars.get(i + 1), Some(next) if next.is_lowercase());
+            if prev.is_lowercase() || prev.is_ascii_digit() || acronym_end {
+                snake.push('_');
+            }
+        }
+        snake.extend(ch.to_lowercase());
+    }
+    snake
+}
+
+// How a field's type relates to `std::option::Option`.
+enum OptionType<'a> {
+    // `Option<T>` or `std::option::Option<T>` and the like, with its `T`.
+    Option(&'a Type),
+    // A path such as `my::Option<T>` whose last segment looks like Option
+    // but that is not known to name it.
+    Ambiguous(&'a Type),
+    NotOption,
+}
+
+fn option_type(ty: &Type) -> OptionType<'_> {
+    if let Type::Path(type_path) = ty {
+        if let Some(segment) = type_path.path.segments.last() {
+            if segment.ident != "Option" {
+                return OptionType::NotOption;
+            }
+            if let PathArguments::AngleBracketed(ref args) = segment.arguments {
+                if let Some(syn::GenericArgument::Type(inner)) = args.args.first() {
+                    let known = is_std_path(type_path);
+                    return if known {
+                        OptionType::Option(inner)
+                    } else {
+                        OptionType::Ambiguous(inner)
+                    };
+                }
+            }
+        }
+    }
+    OptionType::NotOption
+}
+
+// Whether a type's path names an item of the standard library, assuming that
+// a path without a prefix refers to the prelude.
+fn is_std_path(type_path: &syn::TypePath) -> bool {
+    let segments = &type_path.path.segments;
+    type_path.qself.is_none()
+        && match segments.first() {
+            Some(_) if segments.len() == 1 => type_path.path.leading_colon.is_none(),
+            Some(first) => first.ident == "std" || first.ident == "core" || first.ident == "alloc",
+            None => false,
+        }
+}
+
+// The builder derived for a struct type, e.g. `config::ServerBuilder<T>` for
+// `config::Server<T>`.
+fn sub_builder_type(ty: &Type) -> Option<Type> {
+    if let Type::Path(type_path) = ty {
+        if type_path.qself.is_none() && matches!(option_type(ty), OptionType::NotOption) {
+            let mut builder_ty = type_path.clone();
+            if let Some(segment) = builder_ty.path.segments.last_mut() {
+                segment.ident =
+                    syn::Ident::new(&format!("{}Builder", segment.ident), segment.ident.span());
+                return Some(Type::Path(builder_ty));
+            }
+        }
+    }
+    None
+}
+
+// The item type of the standard library collections, which is what an `each`
+// setter adds to them.
+fn collection_item(ty: &Type) -> Option<EachItem> {
+    if let Type::Path(type_path) = ty {
+        if !is_std_path(type_path) {
+            return None;
+        }
+        if let Some(seg) = type_path.path.segments.last() {
+            if let PathArguments::AngleBracketed(ref args) = seg.arguments {
+                let mut types = args.args.iter().filter_map(|arg| match arg {
+                    syn::GenericArgument::Type(ty) => Some(ty.clone()),
+                    _ => None,
+                });
+                match seg.ident.to_string().as_str() {
+                    "Vec" | "VecDeque" | "LinkedList" | "BinaryHeap" | "HashSet" | "BTreeSet" => {
+                        return types.next().map(|item| EachItem::Value(Box::new(item)));
+                    }
+                    "HashMap" | "BTreeMap" => {
+                        if let (Some(key), Some(value)) = (types.next(), types.next()) {
+                            return Some(EachItem::Entry(Box::new(key), Box::new(value)));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    None
 }
@@ -0,0 +1,281 @@
+// Expansion for `#[builder(typestate)]`.
+//
+// Instead of storing every required field as an Option and checking it in
+// build(), the builder gets one extra type parameter per required field. The
+// parameter is `()` while the field is unset and becomes the field's type once
+// its setter has been called, so build() is only implemented for the builder
+// type in which every required field has been set:
+//
+//     pub struct CommandBuilder<__S0, __S1> {
+//         executable: __S0,
+//         current_dir: ::core::option::Option<String>,
+//         ...
+//         __marker: ::core::marker::PhantomData<fn() -> Command>,
+//     }
+//
+//     impl CommandBuilder<String, Vec<String>> {
+//         pub fn build(self) -> Command { ... }
+//     }
+
+use crate::{field_setters, try_setter, BuilderTarget, FieldInfo, Pattern, StructAttrs};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{DeriveInput, GenericParam, Generics, Ident};
+
+pub(crate) fn expand(
+    input: &DeriveInput,
+    struct_attrs: &StructAttrs,
+    target: &BuilderTarget,
+    field_infos: &[FieldInfo],
+) -> TokenStream {
+    let name = &input.ident;
+    let builder_ident = &target.builder_ident;
+    let path = &target.path;
+    let constructor = &target.constructor;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let struct_args = generic_args(&input.generics);
+
+    // One state parameter per required field, in field order.
+    let states: Vec<Option<Ident>> = field_infos
+        .iter()
+        .scan(0usize, |index, info| {
+            Some(if info.is_required() {
+                *index += 1;
+                Some(format_ident!("__S{}", *index - 1))
+            } else {
+                None
+            })
+        })
+        .collect();
+    let state_params: Vec<&Ident> = states.iter().flatten().collect();
+
+    let builder_generics = builder_generics(&input.generics, &state_params);
+    let (builder_impl_generics, builder_ty_generics, _) = builder_generics.split_for_impl();
+
+    // Skipped fields are left out of the builder and get their default in
+    // build().
+    let stored: Vec<(&FieldInfo, &Option<Ident>)> = field_infos
+        .iter()
+        .zip(&states)
+        .filter(|(info, _)| !info.skip)
+        .collect();
+
+    let builder_fields = stored.iter().map(|(info, state)| {
+        let name = &info.name;
+        if let Some(state) = state {
+            quote! { #name: #state, }
+        } else if info.each.is_some() || info.optional {
+            let ty = info.ty;
+            quote! { #name: #ty, }
+        } else {
+            let ty = info.ty;
+            quote! { #name: ::core::option::Option<#ty>, }
+        }
+    });
+
+    let builder_init = stored.iter().map(|(info, state)| {
+        let name = &info.name;
+        if state.is_some() {
+            quote! { #name: (), }
+        } else if let (Some(_), Some(default)) = (&info.each, &info.default) {
+            quote! { #name: #default, }
+        } else if info.each.is_some() {
+            quote! { #name: ::core::default::Default::default(), }
+        } else {
+            quote! { #name: ::core::option::Option::None, }
+        }
+    });
+    let unset_states = state_params.iter().map(|_| quote!(()));
+
+    let setters = stored.iter().map(|(info, state)| {
+        let name = &info.name;
+        let ty = info.ty;
+        let vis = &info.setter_vis;
+
+        if let Some(state) = state {
+            let next_states = state_params.iter().map(|param| {
+                if *param == state {
+                    quote!(#ty)
+                } else {
+                    quote!(#param)
+                }
+            });
+            let moved_fields =
+                stored
+                    .iter()
+                    .filter(|(other, _)| other.name != *name)
+                    .map(|(other, _)| {
+                        let other = &other.name;
+                        quote! { #other: self.#other, }
+                    });
+            let (arg_ty, value) = info.setter_arg(name, ty);
+            let next = quote!(#builder_ident<#(#struct_args,)* #(#next_states),*>);
+            let try_setter = try_setter(info, ty, &quote!(self), &next);
+            let docs = info.setter_docs();
+            quote! {
+                #docs
+                #vis fn #name(self, #name: #arg_ty) -> #next {
+                    #builder_ident {
+                        #(#moved_fields)*
+                        #name: #value,
+                        __marker: ::core::marker::PhantomData,
+                    }
+                }
+
+                #try_setter
+            }
+        } else {
+            field_setters(info, Pattern::Owned)
+        }
+    });
+
+    let set_states: Vec<_> = field_infos
+        .iter()
+        .filter(|info| info.is_required())
+        .map(|info| info.ty)
+        .collect();
+    let build_fields = field_infos.iter().map(|info| {
+        let name = &info.name;
+        let member = &info.member;
+        match &info.default {
+            Some(default) if info.skip => quote! { #member: #default, },
+            Some(default) if info.each.is_none() && info.optional => {
+                quote! { #member: self.#name.or_else(|| #default), }
+            }
+            Some(default) if info.each.is_none() => {
+                quote! { #member: self.#name.unwrap_or_else(|| #default), }
+            }
+            _ => quote! { #member: self.#name, },
+        }
+    });
+
+    // A value converts into the builder state in which every required field
+    // has been set.
+    let to_builder = if struct_attrs.to_builder.is_some() {
+        let from_fields = stored.iter().map(|(info, _)| {
+            let name = &info.name;
+            let member = &info.member;
+            if info.is_required() || info.each.is_some() || info.optional {
+                quote! { #name: value.#member, }
+            } else {
+                quote! { #name: ::core::option::Option::Some(value.#member), }
+            }
+        });
+        quote! {
+            impl #impl_generics #name #ty_generics #where_clause {
+                /// Returns a builder with every field set from this value.
+                pub fn to_builder(&self) -> #builder_ident<#(#struct_args,)* #(#set_states),*>
+                where
+                    Self: ::core::clone::Clone,
+                {
+                    ::core::convert::From::from(::core::clone::Clone::clone(self))
+                }
+            }
+
+            impl #impl_generics ::core::convert::From<#name #ty_generics>
+                for #builder_ident<#(#struct_args,)* #(#set_states),*> #where_clause
+            {
+                fn from(value: #name #ty_generics) -> Self {
+                    #builder_ident {
+                        #(#from_fields)*
+                        __marker: ::core::marker::PhantomData,
+                    }
+                }
+            }
+        }
+    } else {
+        quote!()
+    };
+
+    let derives = struct_attrs.builder_derives(false);
+    let vis = struct_attrs.vis();
+    let build_fn = struct_attrs.build_fn_name();
+
+    let builder_docs = target.builder_docs(field_infos);
+    let constructor_docs = target.constructor_docs();
+    let build_doc = format!(
+        "Builds a [`{}`]. Only available once every required field has been set.",
+        target.doc_path()
+    );
+
+    quote! {
+        #builder_docs
+        #derives
+        #vis struct #builder_ident #builder_generics #where_clause {
+            #(#builder_fields)*
+            // The struct's parameters may only appear in required fields,
+            // which are represented by state parameters until they are set.
+            __marker: ::core::marker::PhantomData<fn() -> #name #ty_generics>,
+        }
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            #constructor_docs
+            #vis fn #constructor() -> #builder_ident<#(#struct_args,)* #(#unset_states),*> {
+                #builder_ident {
+                    #(#builder_init)*
+                    __marker: ::core::marker::PhantomData,
+                }
+            }
+        }
+
+        impl #builder_impl_generics #builder_ident #builder_ty_generics #where_clause {
+            #(#setters)*
+        }
+
+        impl #impl_generics #builder_ident<#(#struct_args,)* #(#set_states),*> #where_clause {
+            #[doc = #build_doc]
+            pub fn #build_fn(self) -> #name #ty_generics {
+                #path {
+                    #(#build_fields)*
+                }
+            }
+        }
+
+        #to_builder
+    }
+}
+
+// The struct's own generics followed by the state parameters. Defaults are
+// dropped because the appended parameters have none and defaulted parameters
+// must be trailing.
+fn builder_generics(generics: &Generics, state_params: &[&Ident]) -> Generics {
+    let mut generics = generics.clone();
+    for param in &mut generics.params {
+        match param {
+            GenericParam::Type(param) => {
+                param.default = None;
+            }
+            GenericParam::Const(param) => {
+                param.default = None;
+            }
+            GenericParam::Lifetime(_) => {}
+        }
+    }
+    for state in state_params {
+        generics.params.push(syn::parse_quote!(#state));
+    }
+    generics
+}
+
+// The struct's generic parameters as they would be written in argument
+// position, e.g. `'a, T, N` for `<'a, T: Clone, const N: usize>`.
+fn generic_args(generics: &Generics) -> Vec<TokenStream> {
+    generics
+        .params
+        .iter()
+        .map(|param| match param {
+            GenericParam::Lifetime(param) => {
+                let lifetime = &param.lifetime;
+                quote!(#lifetime)
+            }
+            GenericParam::Type(param) => {
+                let ident = &param.ident;
+                quote!(#ident)
+            }
+            GenericParam::Const(param) => {
+                let ident = &param.ident;
+                quote!(#ident)
+            }
+        })
+        .collect()
+}
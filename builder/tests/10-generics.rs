@@ -0,0 +1,43 @@
+// Structs with type, lifetime and const parameters, as well as where-clauses,
+// should derive a builder that carries the same generics. The builder type is
+// named with the same parameters as the struct, e.g. RequestBuilder<'a, T, N>.
+
+use derive_builder::Builder;
+use std::fmt::Debug;
+
+pub trait Body: Debug {}
+
+impl Body for String {}
+
+#[derive(Debug, Builder)]
+pub struct Request<'a, T: Body, const N: usize>
+where
+    T: Clone,
+{
+    path: &'a str,
+    body: T,
+    headers: [u8; N],
+    #[builder(each = "tag")]
+    tags: Vec<&'a str>,
+    timeout: Option<u64>,
+}
+
+fn main() {
+    let path = String::from("/index.html");
+    let request: Request<'_, String, 2> = Request::builder()
+        .path(&path)
+        .body("hello".to_owned())
+        .headers([1, 2])
+        .tag("a")
+        .tag("b")
+        .build()
+        .unwrap();
+
+    assert_eq!(request.path, "/index.html");
+    assert_eq!(request.body, "hello");
+    assert_eq!(request.headers, [1, 2]);
+    assert_eq!(request.tags, vec!["a", "b"]);
+    assert_eq!(request.timeout, None);
+
+    let _builder: RequestBuilder<'_, String, 0> = Request::builder();
+}
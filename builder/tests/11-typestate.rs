@@ -0,0 +1,46 @@
+// With #[builder(typestate)] the builder records in its type which required
+// fields have been set. Setters take the builder by value and return a builder
+// of a new type, and build() only exists once every required field was set,
+// at which point it returns the struct directly rather than a Result.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Command<T> {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    env: Vec<String>,
+    current_dir: Option<String>,
+    payload: T,
+}
+
+fn main() {
+    // Required setters may be called in any order.
+    let command: Command<u8> = Command::builder()
+        .payload(7)
+        .arg("build".to_owned())
+        .executable("cargo".to_owned())
+        .env(vec![])
+        .arg("--release".to_owned())
+        .build();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build", "--release"]);
+    assert!(command.env.is_empty());
+    assert_eq!(command.current_dir, None);
+    assert_eq!(command.payload, 7);
+
+    // Setting a required field twice keeps the last value.
+    let command = Command::builder()
+        .executable("rustc".to_owned())
+        .env(vec![])
+        .payload(())
+        .current_dir("..".to_owned())
+        .executable("cargo".to_owned())
+        .build();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.current_dir.as_deref(), Some(".."));
+}
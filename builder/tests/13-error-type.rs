@@ -0,0 +1,63 @@
+// build() reports failures through a generated error enum named after the
+// builder, so callers can match on which field was missing instead of
+// inspecting a message string. The enum implements Display and
+// std::error::Error.
+//
+// With #[builder(error = "...")] build() returns the given error type instead,
+// which must implement From<{Name}BuilderError>.
+
+use derive_builder::Builder;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    current_dir: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum AppError {
+    Config(String),
+}
+
+impl From<ServerBuilderError> for AppError {
+    fn from(err: ServerBuilderError) -> Self {
+        AppError::Config(err.to_string())
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AppError::Config(message) => write!(f, "invalid config: {}", message),
+        }
+    }
+}
+
+#[derive(Builder)]
+#[builder(error = "AppError")]
+pub struct Server {
+    host: String,
+    port: u16,
+}
+
+fn main() {
+    let err = Command::builder().current_dir("..".to_owned()).build().err().unwrap();
+    assert!(matches!(err, CommandBuilderError::UninitializedField("executable")));
+    assert_eq!(err.missing_fields(), ["executable"]);
+    assert_eq!(err.to_string(), "Field executable is not set");
+
+    let boxed: Box<dyn Error> = Box::new(err);
+    assert_eq!(boxed.to_string(), "Field executable is not set");
+
+    let validation = CommandBuilderError::from("bad path".to_owned());
+    assert!(matches!(validation, CommandBuilderError::ValidationError(ref m) if m == "bad path"));
+
+    let err: AppError = Server::builder().port(8080).build().err().unwrap();
+    assert_eq!(err.to_string(), "invalid config: Field host is not set");
+
+    let server = Server::builder().host("localhost".to_owned()).port(8080).build().unwrap();
+    assert_eq!(server.host, "localhost");
+    assert_eq!(server.port, 8080);
+}
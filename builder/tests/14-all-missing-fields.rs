@@ -0,0 +1,40 @@
+// When several required fields are unset, build() reports all of them in a
+// single UninitializedFields error rather than stopping at the first one; a
+// single missing field of the builder's own is still reported as
+// UninitializedField. The builder is left untouched so the caller can fill in
+// the gaps and try again.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    env: Vec<String>,
+    current_dir: Option<String>,
+    timeout: u64,
+}
+
+fn main() {
+    let mut builder = Command::builder();
+    builder.current_dir("..".to_owned());
+
+    let err = builder.build().err().unwrap();
+    assert_eq!(err.missing_fields(), ["executable", "env", "timeout"]);
+    assert!(matches!(
+        &err,
+        CommandBuilderError::UninitializedFields(fields) if fields.len() == 3
+    ));
+    assert_eq!(err.to_string(), "Fields executable, env, timeout are not set");
+
+    builder.executable("cargo".to_owned()).env(vec![]);
+    let err = builder.build().err().unwrap();
+    assert_eq!(err.missing_fields(), ["timeout"]);
+    assert_eq!(err.to_string(), "Field timeout is not set");
+
+    let command = builder.timeout(30).build().unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.current_dir.as_deref(), Some(".."));
+    assert_eq!(command.timeout, 30);
+}
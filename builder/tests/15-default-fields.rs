@@ -0,0 +1,81 @@
+// Fields marked #[builder(default)] fall back to Default::default() when their
+// setter is not called, and #[builder(default = "...")] falls back to the
+// given expression, which may be any Rust expression. Neither kind of field
+// is reported as missing by build().
+//
+// Putting #[builder(default)] on the struct applies Default::default() to
+// every field that would otherwise be required.
+
+use derive_builder::Builder;
+
+fn default_port() -> u16 {
+    8080
+}
+
+#[derive(Builder)]
+pub struct Server {
+    host: String,
+    #[builder(default = "default_port()")]
+    port: u16,
+    #[builder(default)]
+    workers: usize,
+    #[builder(default = "Some(\"/tmp\".to_owned())")]
+    root: Option<String>,
+    #[builder(each = "alias", default = "vec![\"localhost\".to_owned()]")]
+    aliases: Vec<String>,
+    #[builder(default = "[0; 4]")]
+    buckets: [u8; 4],
+    #[builder(default = "{ let base = 2; base * 30 }")]
+    keep_alive: u32,
+}
+
+#[derive(Builder)]
+#[builder(default)]
+pub struct Limits {
+    max_connections: u32,
+    #[builder(default = "30")]
+    timeout: u64,
+    name: String,
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Client {
+    url: String,
+    #[builder(default = "3")]
+    retries: u8,
+}
+
+fn main() {
+    let server = Server::builder().host("example.com".to_owned()).build().unwrap();
+    assert_eq!(server.port, 8080);
+    assert_eq!(server.workers, 0);
+    assert_eq!(server.root.as_deref(), Some("/tmp"));
+    assert_eq!(server.aliases, vec!["localhost"]);
+    assert_eq!(server.buckets, [0; 4]);
+    assert_eq!(server.keep_alive, 60);
+
+    let server = Server::builder()
+        .host("example.com".to_owned())
+        .port(443)
+        .workers(4)
+        .root("/srv".to_owned())
+        .alias("www".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(server.port, 443);
+    assert_eq!(server.workers, 4);
+    assert_eq!(server.root.as_deref(), Some("/srv"));
+    assert_eq!(server.aliases, vec!["localhost", "www"]);
+
+    let err = Server::builder().build().err().unwrap();
+    assert_eq!(err.missing_fields(), ["host"]);
+
+    let limits = Limits::builder().max_connections(10).build().unwrap();
+    assert_eq!(limits.max_connections, 10);
+    assert_eq!(limits.timeout, 30);
+    assert_eq!(limits.name, "");
+
+    let client = Client::builder().url("https://example.com".to_owned()).build();
+    assert_eq!(client.retries, 3);
+}
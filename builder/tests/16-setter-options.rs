@@ -0,0 +1,63 @@
+// #[builder(setter(into))] makes a setter accept any `impl Into<T>`, so
+// callers can write .name("x") instead of .name("x".to_owned()). It can be
+// given on a single field or on the struct, where it applies to every setter
+// and can be turned off again per field with setter(into = false).
+//
+// Setters of Option<T> fields take a T by default. With
+// setter(strip_option = false) they take the Option<T> itself, so a field can
+// be explicitly reset to None. Combined with `into`, such a setter accepts
+// either a T or an Option<T>.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    #[builder(setter(into))]
+    executable: String,
+    #[builder(each = "arg", setter(into))]
+    args: Vec<String>,
+    #[builder(setter(into, strip_option = false))]
+    current_dir: Option<String>,
+    #[builder(setter(strip_option = false))]
+    timeout: Option<u64>,
+}
+
+#[derive(Builder)]
+#[builder(setter(into))]
+pub struct Server {
+    host: String,
+    root: Option<String>,
+    #[builder(setter(into = false))]
+    port: u16,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo")
+        .arg("build")
+        .arg(String::from("--release"))
+        .current_dir("..".to_owned())
+        .timeout(None)
+        .build()
+        .unwrap();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build", "--release"]);
+    assert_eq!(command.current_dir.as_deref(), Some(".."));
+    assert_eq!(command.timeout, None);
+
+    let command = Command::builder()
+        .executable("cargo")
+        .current_dir(None)
+        .timeout(Some(30))
+        .build()
+        .unwrap();
+
+    assert_eq!(command.current_dir, None);
+    assert_eq!(command.timeout, Some(30));
+
+    let server = Server::builder().host("localhost").root("/srv").port(80).build().unwrap();
+    assert_eq!(server.host, "localhost");
+    assert_eq!(server.root.as_deref(), Some("/srv"));
+    assert_eq!(server.port, 80);
+}
@@ -0,0 +1,65 @@
+// #[builder(pattern = "...")] chooses how setters and build() take the
+// builder:
+//
+//   - "mutable" (the default): setters take &mut self and return &mut Self,
+//     build(&mut self) moves the values out of the builder.
+//   - "owned": setters take self and return Self, build(self) consumes the
+//     builder and moves every value into the struct without cloning.
+//   - "immutable": setters take &self and return a modified copy, build(&self)
+//     clones the values so the builder can be reused.
+
+use derive_builder::Builder;
+
+// Deliberately not Clone.
+#[derive(Debug, PartialEq)]
+pub struct Arg(&'static str);
+
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<Arg>,
+    current_dir: Option<String>,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "immutable")]
+pub struct Server {
+    host: String,
+    port: u16,
+    // Named like a local variable of the setters.
+    #[builder(default)]
+    new: bool,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "mutable")]
+pub struct Client {
+    #[builder(each = "header")]
+    headers: Vec<Arg>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .arg(Arg("build"))
+        .arg(Arg("--release"))
+        .build()
+        .unwrap();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec![Arg("build"), Arg("--release")]);
+    assert_eq!(command.current_dir, None);
+
+    let base = Server::builder().host("localhost".to_owned());
+    let http = base.port(80).build().unwrap();
+    let https = base.port(443).build().unwrap();
+    assert_eq!((http.host.as_str(), http.port), ("localhost", 80));
+    assert_eq!((https.host.as_str(), https.port), ("localhost", 443));
+    assert_eq!(base.build().err().unwrap().missing_fields(), ["port"]);
+    assert!(base.port(80).new(true).build().unwrap().new);
+
+    let client = Client::builder().header(Arg("accept")).build().unwrap();
+    assert_eq!(client.headers, vec![Arg("accept")]);
+}
@@ -0,0 +1,70 @@
+// `each` is not limited to Vec. Sets, deques and other standard collections
+// get a setter taking one item, maps get a setter taking a key and a value,
+// and any other collection implementing Default, Extend and IntoIterator can
+// be used by naming its item type with each(name = "...", item = "...") and
+// how merge() combines it with merge = "...".
+
+use derive_builder::Builder;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Tags(Vec<String>);
+
+impl Extend<String> for Tags {
+    fn extend<I: IntoIterator<Item = String>>(&mut self, iter: I) {
+        self.0.extend(iter);
+    }
+}
+
+impl IntoIterator for Tags {
+    type Item = String;
+    type IntoIter = std::vec::IntoIter<String>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+#[derive(Builder)]
+pub struct Request {
+    #[builder(each = "header")]
+    headers: HashMap<String, String>,
+    #[builder(each = "param", setter(into))]
+    params: BTreeMap<String, u32>,
+    #[builder(each = "flag")]
+    flags: HashSet<&'static str>,
+    #[builder(each = "feature")]
+    features: BTreeSet<u8>,
+    #[builder(each = "step")]
+    steps: VecDeque<u8>,
+    #[builder(each = "attempt")]
+    attempts: std::collections::LinkedList<u8>,
+    #[builder(each(name = "tag", item = "String"), merge = "append")]
+    tags: Tags,
+}
+
+fn main() {
+    let request = Request::builder()
+        .header("accept".to_owned(), "text/html".to_owned())
+        .header("host".to_owned(), "example.com".to_owned())
+        .param("page", 2u32)
+        .flag("verbose")
+        .flag("verbose")
+        .feature(3)
+        .feature(1)
+        .step(1)
+        .step(2)
+        .attempt(9)
+        .tag("a".to_owned())
+        .build()
+        .unwrap();
+
+    assert_eq!(request.headers.len(), 2);
+    assert_eq!(request.headers["host"], "example.com");
+    assert_eq!(request.params["page"], 2);
+    assert_eq!(request.flags.len(), 1);
+    assert_eq!(request.features.into_iter().collect::<Vec<_>>(), [1, 3]);
+    assert_eq!(request.steps, [1, 2]);
+    assert_eq!(request.attempts.front(), Some(&9));
+    assert_eq!(request.tags, Tags(vec!["a".to_owned()]));
+}
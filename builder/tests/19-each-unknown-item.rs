@@ -0,0 +1,22 @@
+// The item type of a collection other than the standard ones cannot be
+// inferred from its tokens, so `each` on such a field without an explicit
+// `item` is an error pointing at the field's type.
+
+use derive_builder::Builder;
+
+#[derive(Default)]
+pub struct Tags(Vec<String>);
+
+impl Extend<String> for Tags {
+    fn extend<I: IntoIterator<Item = String>>(&mut self, iter: I) {
+        self.0.extend(iter);
+    }
+}
+
+#[derive(Builder)]
+pub struct Request {
+    #[builder(each = "tag")]
+    tags: Tags,
+}
+
+fn main() {}
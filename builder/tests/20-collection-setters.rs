@@ -0,0 +1,55 @@
+// Besides the one-item `each` setter, a collection field gets a setter that
+// replaces the whole collection (unless it would share the one-item setter's
+// name) and an extend_<field> method appending everything from an iterator.
+// A raw identifier field like `r#type` gets `extend_type`.
+
+use derive_builder::Builder;
+use std::collections::HashMap;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    #[builder(each = "env")]
+    env: HashMap<String, String>,
+    #[builder(each = "kind")]
+    r#type: Vec<String>,
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Script {
+    #[builder(each = "line")]
+    lines: Vec<String>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .arg("ignored".to_owned())
+        .args(vec!["build".to_owned()])
+        .arg("--release".to_owned())
+        .extend_args(vec!["--locked".to_owned(), "--offline".to_owned()])
+        .env("A".to_owned(), "1".to_owned())
+        .extend_env([("B".to_owned(), "2".to_owned())])
+        .kind("bin".to_owned())
+        .extend_type(["lib".to_owned()])
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        command.args,
+        ["build", "--release", "--locked", "--offline"]
+    );
+    assert_eq!(command.env.len(), 2);
+    assert_eq!(command.env["B"], "2");
+    assert_eq!(command.r#type, ["bin", "lib"]);
+
+    let script = Script::builder()
+        .lines(vec!["set -e".to_owned()])
+        .line("make".to_owned())
+        .extend_lines(["make install".to_owned()])
+        .build();
+    assert_eq!(script.lines, ["set -e", "make", "make install"]);
+}
@@ -0,0 +1,163 @@
+// #[builder(build_fn(validate = "path::to::fn"))] runs the given function on
+// the built value before build() returns it. The function returns
+// Result<(), E> for any E: Display, and an error is reported by build() as
+// the ValidationError variant of the builder error, or converted into a
+// custom #[builder(error = "...")] type like any other build error.
+//
+// A build that fails validation leaves the builder as it was, so that the
+// offending fields can be corrected and build() called again. This includes
+// a validation failure in a sub-builder. Fields may have any name, including
+// those of the variables used by the generated build().
+
+#![deny(non_shorthand_field_patterns)]
+
+use derive_builder::Builder;
+
+#[derive(Debug, Builder)]
+#[builder(build_fn(validate = "validate::range"))]
+pub struct Range {
+    min: u32,
+    max: u32,
+}
+
+#[derive(Debug, Builder)]
+#[builder(build_fn(validate = "Window::validate"))]
+pub struct Window {
+    range: Option<u32>,
+    #[builder(default = "10")]
+    step: u32,
+    #[builder(each = "tag")]
+    tags: Vec<String>,
+    #[builder(sub_builder)]
+    limits: Range,
+}
+
+impl Window {
+    fn validate(&self) -> Result<(), &'static str> {
+        if self.step == 0 {
+            Err("step must not be 0")
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[derive(Debug, Builder)]
+#[builder(build_fn(validate = "Names::validate"))]
+pub struct Names {
+    err: String,
+    unset: Option<u32>,
+    #[builder(default = "5")]
+    built: u32,
+    #[builder(sub_builder)]
+    value: Range,
+}
+
+impl Names {
+    fn validate(&self) -> Result<(), &'static str> {
+        if self.err.is_empty() {
+            Err("err must not be empty")
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[derive(Debug, Builder)]
+#[builder(pattern = "owned", build_fn(validate = "Server::validate"))]
+pub struct Server {
+    host: String,
+    port: u16,
+}
+
+impl Server {
+    fn validate(&self) -> Result<(), &'static str> {
+        if self.port == 0 {
+            Err("port must not be 0")
+        } else {
+            Ok(())
+        }
+    }
+}
+
+mod validate {
+    pub fn range(range: &super::Range) -> Result<(), String> {
+        if range.min <= range.max {
+            Ok(())
+        } else {
+            Err(format!(
+                "min {} is greater than max {}",
+                range.min, range.max
+            ))
+        }
+    }
+}
+
+fn main() {
+    let range = Range::builder().min(1).max(5).build().unwrap();
+    assert_eq!((range.min, range.max), (1, 5));
+
+    let err = Range::builder().min(5).max(1).build().unwrap_err();
+    assert!(matches!(err, RangeBuilderError::ValidationError(_)));
+    assert_eq!(err.to_string(), "min 5 is greater than max 1");
+    assert!(err.missing_fields().is_empty());
+
+    // Missing fields are reported before validation runs.
+    let err = Range::builder().min(5).build().unwrap_err();
+    assert_eq!(err.missing_fields(), ["max"]);
+
+    let err = Server::builder()
+        .host("localhost".to_owned())
+        .port(0)
+        .build()
+        .unwrap_err();
+    assert_eq!(err.to_string(), "port must not be 0");
+
+    let mut builder = Range::builder();
+    builder.min(5).max(1);
+    assert!(builder.build().is_err());
+    assert_eq!(builder.get_min(), Some(&5));
+    let range = builder.max(10).build().unwrap();
+    assert_eq!((range.min, range.max), (5, 10));
+
+    let mut builder = Window::builder();
+    builder.range(3).step(0).tag("a".to_owned());
+    builder.limits().min(1).max(2);
+    let err = builder.build().unwrap_err();
+    assert_eq!(err.to_string(), "step must not be 0");
+    assert_eq!(builder.get_range(), Some(&3));
+    assert_eq!(builder.get_step(), Some(&0));
+    assert_eq!(builder.get_tags(), &["a"]);
+    assert_eq!(builder.get_limits().get_max(), Some(&2));
+
+    builder.clear_step();
+    builder.limits().max(0);
+    let err = builder.build().unwrap_err();
+    assert_eq!(err.to_string(), "limits: min 1 is greater than max 0");
+    assert_eq!(builder.get_step(), None);
+    assert_eq!(builder.get_tags(), &["a"]);
+    assert_eq!(builder.get_limits().get_min(), Some(&1));
+
+    builder.limits().max(4);
+    let window = builder.build().unwrap();
+    assert_eq!(window.range, Some(3));
+    assert_eq!(window.step, 10);
+    assert_eq!(window.tags, ["a"]);
+    assert_eq!((window.limits.min, window.limits.max), (1, 4));
+
+    let mut builder = Names::builder();
+    builder.err(String::new()).unset(1);
+    builder.value().min(1).max(2);
+    let err = builder.build().unwrap_err();
+    assert_eq!(err.to_string(), "err must not be empty");
+    assert_eq!(builder.get_err().map(String::as_str), Some(""));
+    assert_eq!(builder.get_unset(), Some(&1));
+    assert_eq!(builder.get_built(), None);
+    assert_eq!(builder.get_value().get_min(), Some(&1));
+
+    let names = builder.err("e".to_owned()).build().unwrap();
+    assert_eq!(
+        (names.err.as_str(), names.unset, names.built),
+        ("e", Some(1), 5)
+    );
+}
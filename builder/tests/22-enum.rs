@@ -0,0 +1,110 @@
+// Deriving Builder on an enum generates one builder per variant with fields.
+// For a variant `Circle` of `Shape`, the builder is `ShapeCircleBuilder`, it is
+// created by `Shape::circle_builder()`, and its build() returns a `Shape`. An
+// acronym in a variant's name is one word, so `HTTPGet` gets
+// `http_get_builder()`. The fields of a tuple variant are set like those of a
+// tuple struct, with `_0`, `_1`, ... Unit variants get no builder. All variant
+// builders share a single `ShapeBuilderError` type.
+
+use derive_builder::Builder;
+
+#[derive(Debug, PartialEq, Builder)]
+pub enum Shape<T> {
+    Circle {
+        radius: f64,
+        label: Option<String>,
+    },
+    RoundedRect {
+        width: f64,
+        height: f64,
+        #[builder(default)]
+        corner_radius: f64,
+    },
+    Polygon {
+        #[builder(each = "point")]
+        points: Vec<(T, T)>,
+    },
+    Empty,
+    Scaled(f64, Box<Shape<T>>),
+}
+
+#[derive(Debug, PartialEq, Builder)]
+pub enum Request {
+    HTTPGet { url: String },
+    Retry2Times(String),
+}
+
+#[derive(Debug, PartialEq, Builder)]
+#[builder(typestate)]
+pub enum Command {
+    Run { program: String },
+    Exit { code: i32 },
+}
+
+fn main() {
+    let circle: Shape<i32> = Shape::circle_builder().radius(1.5).build().unwrap();
+    assert_eq!(
+        circle,
+        Shape::Circle {
+            radius: 1.5,
+            label: None,
+        }
+    );
+
+    let rect: Shape<i32> = Shape::rounded_rect_builder()
+        .width(2.0)
+        .height(3.0)
+        .build()
+        .unwrap();
+    assert_eq!(
+        rect,
+        Shape::RoundedRect {
+            width: 2.0,
+            height: 3.0,
+            corner_radius: 0.0,
+        }
+    );
+
+    let polygon = Shape::polygon_builder()
+        .point((0, 0))
+        .point((1, 1))
+        .build()
+        .unwrap();
+    assert_eq!(
+        polygon,
+        Shape::Polygon {
+            points: vec![(0, 0), (1, 1)],
+        }
+    );
+
+    let err: ShapeBuilderError = Shape::<i32>::rounded_rect_builder().build().unwrap_err();
+    assert_eq!(err.missing_fields(), ["width", "height"]);
+
+    let _builder: ShapeCircleBuilder<u8> = Shape::circle_builder();
+
+    let scaled = Shape::scaled_builder()
+        ._0(2.0)
+        ._1(Box::new(circle))
+        .build()
+        .unwrap();
+    assert!(matches!(scaled, Shape::Scaled(factor, _) if factor == 2.0));
+
+    let get = Request::http_get_builder()
+        .url("/".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(
+        get,
+        Request::HTTPGet {
+            url: "/".to_owned()
+        }
+    );
+    let retry = Request::retry2_times_builder()
+        ._0("/".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(retry, Request::Retry2Times("/".to_owned()));
+
+    let exit = Command::exit_builder().code(1).build();
+    assert_eq!(exit, Command::Exit { code: 1 });
+}
@@ -0,0 +1,60 @@
+// Tuple structs get positional setters named `_0`, `_1`, ... after the index
+// of each field, or any name given with #[builder(name = "...")]. Missing
+// fields are reported under the same names. Other builder methods drop the
+// underscore, as in `get_0`, `clear_1`, `try_0` and `extend_1`, so that they
+// are snake case.
+
+#![deny(non_snake_case)]
+
+use derive_builder::Builder;
+
+#[derive(Debug, PartialEq, Builder)]
+pub struct Point(i32, i32, Option<String>);
+
+#[derive(Debug, PartialEq, Builder)]
+pub struct Endpoint(
+    #[builder(name = "host", setter(into))] String,
+    #[builder(name = "port", default = "80")] u16,
+);
+
+#[derive(Debug, PartialEq, Builder)]
+pub struct Samples(
+    #[builder(try_setter)] u8,
+    #[builder(each = "sample")] Vec<u8>,
+);
+
+#[derive(Debug, PartialEq, Builder)]
+#[builder(typestate)]
+pub struct UserId(#[builder(name = "id")] u64);
+
+fn main() {
+    let point = Point::builder()._0(1)._1(2).build().unwrap();
+    assert_eq!(point, Point(1, 2, None));
+
+    let err = Point::builder()._1(2).build().unwrap_err();
+    assert_eq!(err.missing_fields(), ["_0"]);
+
+    let endpoint = Endpoint::builder().host("localhost").build().unwrap();
+    assert_eq!(endpoint, Endpoint("localhost".to_owned(), 80));
+
+    let err = Endpoint::builder().port(8080).build().unwrap_err();
+    assert_eq!(err.missing_fields(), ["host"]);
+
+    assert_eq!(UserId::builder().id(7).build(), UserId(7));
+
+    let mut builder = Point::builder();
+    builder._0(1)._1(2);
+    assert_eq!(builder.get_0(), Some(&1));
+    builder.clear_1();
+    assert_eq!(builder.get_1(), None);
+
+    assert!(Samples::builder().try_0(300u32).is_err());
+    let samples = Samples::builder()
+        .try_0(3u32)
+        .unwrap()
+        .sample(1)
+        .extend_1([2, 3])
+        .build()
+        .unwrap();
+    assert_eq!(samples, Samples(3, vec![1, 2, 3]));
+}
@@ -0,0 +1,34 @@
+// Every problem with the builder attributes is reported as a compile error
+// pointing at the offending tokens, and all of them are reported together
+// rather than one per compile. Inputs that cannot have a builder, like unions
+// and unit structs, are errors too rather than a panic in the macro.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(pattern = "borrowed", frobnicate, setter(into))]
+pub struct Command {
+    #[builder(eac = "arg")]
+    args: Vec<String>,
+    #[builder(default = "1 +", setter(onto))]
+    retries: u32,
+    #[builder(each = "tag")]
+    tags: Tags,
+    #[builder(each = "add item")]
+    items: Vec<String>,
+    #[builder(each(name = "1st"))]
+    firsts: Vec<String>,
+}
+
+pub struct Tags;
+
+#[derive(Builder)]
+pub union Bits {
+    int: u32,
+    float: f32,
+}
+
+#[derive(Builder)]
+pub struct Marker;
+
+fn main() {}
@@ -0,0 +1,62 @@
+// #[builder(to_builder)] goes the other way: an existing value can be turned
+// back into a builder with every field already populated, changed, and built
+// again. The value is converted with `From<Command> for CommandBuilder`, and
+// `to_builder(&self)` does the same from a reference for types that are
+// Clone.
+//
+// With #[builder(typestate)] the conversion produces the builder state in
+// which every required field is set, so build() can be called right away.
+
+use derive_builder::Builder;
+
+#[derive(Clone, Debug, PartialEq, Builder)]
+#[builder(to_builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    #[builder(default = "8")]
+    jobs: u32,
+    current_dir: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Builder)]
+#[builder(typestate, to_builder)]
+pub struct Server {
+    host: String,
+    port: u16,
+    #[builder(default)]
+    workers: usize,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .arg("build".to_owned())
+        .current_dir("..".to_owned())
+        .build()
+        .unwrap();
+
+    let release = command
+        .to_builder()
+        .arg("--release".to_owned())
+        .jobs(2)
+        .build()
+        .unwrap();
+    assert_eq!(release.executable, "cargo");
+    assert_eq!(release.args, vec!["build", "--release"]);
+    assert_eq!(release.jobs, 2);
+    assert_eq!(release.current_dir, Some("..".to_owned()));
+
+    let unchanged = CommandBuilder::from(command.clone()).build().unwrap();
+    assert_eq!(unchanged, command);
+
+    let server = Server::builder()
+        .host("localhost".to_owned())
+        .port(8080)
+        .build();
+    let moved = server.to_builder().port(9090).build();
+    assert_eq!(moved.host, "localhost");
+    assert_eq!(moved.port, 9090);
+    assert_eq!(moved.workers, 0);
+}
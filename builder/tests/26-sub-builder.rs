@@ -0,0 +1,133 @@
+// A field whose type also derives Builder can be marked
+// #[builder(sub_builder)]. The parent builder then stores the field's builder
+// rather than the value, hands it out by mutable reference from a method named
+// after the field, and builds it as part of its own build().
+//
+// Required fields missing from a sub-builder are reported by the parent with a
+// dotted path, e.g. `server.port`, alongside the parent's own missing fields,
+// and always in the UninitializedFields variant. A validation error from a
+// sub-builder is reported as a validation error of the parent, prefixed with
+// the field name.
+//
+// The parent and the field's builder may use different patterns and build
+// function names. The field's builder is assumed to be named `{Type}Builder`;
+// one renamed with `builder(name = "...")` is given as
+// `sub_builder = "path::to::Builder"`. With `pattern = "immutable"` the
+// parent clones itself in every setter, so the field's builder must be Clone,
+// which it is if it uses the immutable pattern too.
+
+use derive_builder::Builder;
+
+#[derive(Debug, Builder)]
+pub struct Tls {
+    cert: String,
+    key: Option<String>,
+}
+
+#[derive(Debug, Builder)]
+#[builder(build_fn(validate = "Server::validate"))]
+pub struct Server {
+    host: String,
+    port: u16,
+    #[builder(sub_builder)]
+    tls: Tls,
+}
+
+impl Server {
+    fn validate(&self) -> Result<(), &'static str> {
+        if self.port == 0 {
+            Err("port must not be 0")
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[derive(Debug, Builder)]
+pub struct Service {
+    name: String,
+    #[builder(sub_builder)]
+    server: Server,
+}
+
+#[derive(Debug, Builder)]
+#[builder(name = "LimitsOptions", build_fn(name = "finish"))]
+pub struct Limits {
+    max_connections: u32,
+}
+
+#[derive(Debug, Builder)]
+#[builder(pattern = "owned")]
+pub struct Proxy {
+    upstream: String,
+    #[builder(sub_builder)]
+    tls: Tls,
+    #[builder(sub_builder = "LimitsOptions")]
+    limits: Limits,
+}
+
+#[derive(Debug, Builder)]
+#[builder(pattern = "immutable")]
+pub struct Endpoint {
+    path: String,
+}
+
+#[derive(Debug, Builder)]
+#[builder(pattern = "immutable")]
+pub struct Route {
+    method: String,
+    #[builder(sub_builder)]
+    endpoint: Endpoint,
+}
+
+fn main() {
+    let mut builder = Service::builder();
+    builder.name("api".to_owned());
+    builder.server().host("localhost".to_owned());
+
+    let err = builder.build().unwrap_err();
+    assert_eq!(err.missing_fields(), ["server.port", "server.tls.cert"]);
+    assert_eq!(
+        err.to_string(),
+        "Fields server.port, server.tls.cert are not set"
+    );
+
+    // A nested field is always reported as part of a list, even alone.
+    builder.server().tls().cert("cert.pem".to_owned());
+    let err = builder.build().unwrap_err();
+    assert!(
+        matches!(err, ServiceBuilderError::UninitializedFields(ref fields) if fields == &["server.port"])
+    );
+
+    builder.server().port(0);
+    let err = builder.build().unwrap_err();
+    assert_eq!(err.to_string(), "server: port must not be 0");
+
+    let mut builder = Service::builder();
+    builder.name("api".to_owned());
+    builder.server().host("localhost".to_owned()).port(8443);
+    builder
+        .server()
+        .tls()
+        .cert("cert.pem".to_owned())
+        .key("key.pem".to_owned());
+
+    let service = builder.build().unwrap();
+    assert_eq!(service.name, "api");
+    assert_eq!(service.server.host, "localhost");
+    assert_eq!(service.server.port, 8443);
+    assert_eq!(service.server.tls.cert, "cert.pem");
+    assert_eq!(service.server.tls.key.as_deref(), Some("key.pem"));
+
+    let mut builder = Proxy::builder().upstream("backend".to_owned());
+    builder.tls().cert("cert.pem".to_owned());
+    builder.limits().max_connections(100);
+    let proxy = builder.build().unwrap();
+    assert_eq!(proxy.tls.cert, "cert.pem");
+    assert_eq!(proxy.limits.max_connections, 100);
+
+    let mut builder = Route::builder().method("GET".to_owned());
+    *builder.endpoint() = builder.get_endpoint().path("/health".to_owned());
+    let route = builder.build().unwrap();
+    assert_eq!(route.endpoint.path, "/health");
+}
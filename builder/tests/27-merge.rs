@@ -0,0 +1,126 @@
+// merge() layers one builder over another: every field set in `other`
+// replaces the value in `self`, while fields left unset in `other` keep
+// whatever `self` had. This lets defaults, a config file and command line
+// overrides each fill in a builder of their own before being combined.
+//
+// Collections with an `each` setter are appended to by default. Marking the
+// field #[builder(merge = "replace")] instead replaces the collection when
+// `other` has any items. Sub-builders are merged field by field. A collection
+// with a default is only set in `other` once it has been added to, so merging
+// in a new builder leaves `self` as it was.
+//
+// A collection given an explicit `each(item = "...")` type may not be
+// iterable, so the field has to say how to merge it with
+// #[builder(merge = "...")].
+
+use derive_builder::Builder;
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Tags(Vec<String>);
+
+impl Extend<String> for Tags {
+    fn extend<I: IntoIterator<Item = String>>(&mut self, iter: I) {
+        self.0.extend(iter);
+    }
+}
+
+impl IntoIterator for Tags {
+    type Item = String;
+    type IntoIter = std::vec::IntoIter<String>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+#[derive(Debug, Builder)]
+pub struct Limits {
+    memory: u64,
+    cpus: Option<u32>,
+}
+
+#[derive(Debug, Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    #[builder(each = "env", merge = "replace")]
+    env: Vec<String>,
+    current_dir: Option<String>,
+    #[builder(default = "30")]
+    timeout: u32,
+    #[builder(sub_builder)]
+    limits: Limits,
+    #[builder(each(name = "tag", item = "String"), merge = "append")]
+    tags: Tags,
+}
+
+#[derive(Debug, Builder)]
+pub struct Pool {
+    #[builder(each = "host", default = "vec![\"localhost\".to_owned()]")]
+    hosts: Vec<String>,
+    #[builder(each = "port", default = "vec![80]", merge = "replace")]
+    ports: Vec<u16>,
+}
+
+#[derive(Debug, Builder)]
+#[builder(pattern = "owned")]
+pub struct Server {
+    host: String,
+    port: u16,
+}
+
+fn main() {
+    let mut defaults = Command::builder();
+    defaults
+        .executable("cargo".to_owned())
+        .arg("build".to_owned())
+        .env("RUST_LOG=info".to_owned())
+        .env("RUST_BACKTRACE=1".to_owned())
+        .timeout(60)
+        .tag("ci".to_owned());
+    defaults.limits().memory(1024).cpus(2);
+
+    let mut config = Command::builder();
+    config
+        .arg("--release".to_owned())
+        .current_dir("..".to_owned());
+    config.limits().memory(4096);
+
+    let mut cli = Command::builder();
+    cli.executable("cross".to_owned())
+        .tag("manual".to_owned())
+        .env("RUST_LOG=debug".to_owned());
+
+    let command = defaults.merge(config).merge(cli).build().unwrap();
+    assert_eq!(command.executable, "cross");
+    assert_eq!(command.args, vec!["build", "--release"]);
+    assert_eq!(command.env, vec!["RUST_LOG=debug"]);
+    assert_eq!(command.current_dir, Some("..".to_owned()));
+    assert_eq!(command.timeout, 60);
+    assert_eq!(command.limits.memory, 4096);
+    assert_eq!(command.limits.cpus, Some(2));
+    assert_eq!(
+        command.tags,
+        Tags(vec!["ci".to_owned(), "manual".to_owned()])
+    );
+
+    let mut pool = Pool::builder();
+    pool.port(8080);
+    let pool = pool.merge(Pool::builder()).build().unwrap();
+    assert_eq!(pool.hosts, ["localhost"]);
+    assert_eq!(pool.ports, [80, 8080]);
+
+    let mut base = Pool::builder();
+    base.ports(vec![443]);
+    let mut overrides = Pool::builder();
+    overrides.host("example.com".to_owned());
+    let pool = base.merge(overrides).build().unwrap();
+    assert_eq!(pool.hosts, ["localhost", "example.com"]);
+    assert_eq!(pool.ports, [443]);
+
+    let base = Server::builder().host("localhost".to_owned()).port(80);
+    let server = base.merge(Server::builder().port(8080)).build().unwrap();
+    assert_eq!(server.host, "localhost");
+    assert_eq!(server.port, 8080);
+}
@@ -0,0 +1,80 @@
+// With the `serde` cargo feature enabled, #[builder(serde)] makes the builder
+// derive serde::Deserialize with every field optional. A partial configuration
+// document in any serde format deserializes into a builder, which can then be
+// merged with builders filled in by setters and checked by build() as usual.
+//
+// Keys are the struct's field names, even where a setter has been renamed,
+// and fields marked #[builder(sub_builder)] are nested objects deserialized
+// into their own builder, which needs #[builder(serde)] too. The crate using
+// the derive needs serde as a dependency itself.
+//
+// The builder only derives Deserialize when asked to, because the feature is
+// shared by every crate in the build that uses the derive, and most structs
+// have fields that are not Deserialize.
+
+use derive_builder::Builder;
+use std::time::Instant;
+
+// Not opted in, so its fields need not be Deserialize.
+#[derive(Debug, Builder)]
+pub struct Timer {
+    started: Instant,
+}
+
+#[derive(Debug, Builder)]
+#[builder(serde)]
+pub struct Server {
+    host: String,
+    port: u16,
+}
+
+#[derive(Debug, Builder)]
+#[builder(serde)]
+pub struct Config {
+    name: String,
+    #[builder(each = "tag")]
+    tags: Vec<String>,
+    #[builder(default = "4", name = "worker_count")]
+    workers: usize,
+    log_level: Option<String>,
+    #[builder(sub_builder)]
+    server: Server,
+}
+
+fn main() {
+    assert!(Timer::builder().started(Instant::now()).build().is_ok());
+
+    let document = r#"{
+        "name": "api",
+        "tags": ["internal"],
+        "workers": 8,
+        "server": { "host": "localhost" }
+    }"#;
+    let mut builder: ConfigBuilder = serde_json::from_str(document).unwrap();
+
+    let err = builder.build().unwrap_err();
+    assert_eq!(err.missing_fields(), ["server.port"]);
+
+    let mut overrides = Config::builder();
+    overrides.tag("v2".to_owned()).log_level("debug".to_owned());
+    overrides.server().port(8080);
+
+    let config = builder.merge(overrides).build().unwrap();
+    assert_eq!(config.name, "api");
+    assert_eq!(config.tags, vec!["internal", "v2"]);
+    assert_eq!(config.workers, 8);
+    assert_eq!(config.log_level.as_deref(), Some("debug"));
+    assert_eq!(config.server.host, "localhost");
+    assert_eq!(config.server.port, 8080);
+
+    let mut empty: ConfigBuilder = serde_json::from_str("{}").unwrap();
+    let err = empty.build().unwrap_err();
+    assert_eq!(err.missing_fields(), ["name", "server.host", "server.port"]);
+
+    let mut minimal: ConfigBuilder =
+        serde_json::from_str(r#"{"name": "api", "server": {"host": "::1", "port": 80}}"#).unwrap();
+    let config = minimal.build().unwrap();
+    assert_eq!(config.workers, 4);
+    assert!(config.tags.is_empty());
+    assert_eq!(config.log_level, None);
+}
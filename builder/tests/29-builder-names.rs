@@ -0,0 +1,64 @@
+// The generated API can be adapted to a crate's conventions:
+//
+//     #[builder(name = "...")]              the builder type, in place of
+//                                            {Name}Builder; the error type is
+//                                            then named after it, {...}Error
+//     #[builder(vis = "...")]               the visibility of the builder, its
+//                                            constructor and its error type
+//     #[builder(constructor = "...")]       the constructor, in place of builder()
+//     #[builder(build_fn(name = "..."))]    the build function, in place of build()
+//     #[builder(derive(Debug, Clone, ...))] traits derived for the builder
+//
+// A private struct can thereby keep its builder from being `pub`, which would
+// otherwise leak from the module through the builder's public constructor.
+
+mod config {
+    use derive_builder::Builder;
+
+    #[derive(Debug, Builder)]
+    #[builder(
+        name = "ConfigOptions",
+        vis = "pub(crate)",
+        constructor = "options",
+        build_fn(name = "finish"),
+        derive(Debug, Clone)
+    )]
+    pub(crate) struct Config {
+        pub(crate) name: String,
+        pub(crate) verbose: Option<bool>,
+    }
+
+    #[derive(Debug, Builder)]
+    #[builder(
+        typestate,
+        name = "PointParts",
+        constructor = "parts",
+        build_fn(name = "assemble"),
+        derive(Debug)
+    )]
+    pub struct Point {
+        pub x: i32,
+        pub y: i32,
+    }
+}
+
+use config::{Config, ConfigOptions, ConfigOptionsError, Point};
+
+fn main() {
+    let mut options: ConfigOptions = Config::options();
+    options.verbose(true);
+
+    let snapshot = options.clone();
+    let err: ConfigOptionsError = snapshot.clone().finish().unwrap_err();
+    assert_eq!(err.missing_fields(), ["name"]);
+    assert!(format!("{:?}", snapshot).starts_with("ConfigOptions {"));
+
+    let config = options.name("app".to_owned()).finish().unwrap();
+    assert_eq!(config.name, "app");
+    assert_eq!(config.verbose, Some(true));
+
+    let parts = Point::parts().x(1).y(2);
+    assert!(format!("{:?}", parts).starts_with("PointParts {"));
+    let point = parts.assemble();
+    assert_eq!((point.x, point.y), (1, 2));
+}
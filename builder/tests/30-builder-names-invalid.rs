@@ -0,0 +1,20 @@
+// The builder already implements Default, so it cannot be derived as well.
+// An enum has one builder per variant, so it cannot take a single builder
+// name or constructor.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(derive(Debug, Default))]
+pub struct Command {
+    executable: String,
+}
+
+#[derive(Builder)]
+#[builder(name = "ShapeParts", constructor = "parts")]
+pub enum Shape {
+    Circle { radius: f64 },
+    Square { side: f64 },
+}
+
+fn main() {}
@@ -0,0 +1,72 @@
+// Fields that are not meant to be configured can be kept out of the builder's
+// API:
+//
+//     #[builder(skip)]            no setter and no place in the builder; build()
+//                                 initializes the field with Default::default()
+//     #[builder(skip = "expr")]   the same, initialized with the expression
+//     #[builder(setter(skip))]    no setter, but the field is still stored in the
+//                                 builder, so merge() and to_builder() carry it
+//                                 over; it defaults like a skipped field
+//
+// #[builder(vis = "...")] on a field sets the visibility of its setters, which
+// are otherwise `pub`.
+
+mod cache {
+    use derive_builder::Builder;
+    use std::collections::HashMap;
+
+    #[derive(Clone, Debug, Builder)]
+    #[builder(to_builder)]
+    pub struct Resolver {
+        pub upstream: String,
+        #[builder(vis = "pub(crate)")]
+        pub retries: u32,
+        #[builder(skip)]
+        pub cache: HashMap<String, String>,
+        #[builder(skip = "1")]
+        pub generation: u64,
+        #[builder(setter(skip), default = "3")]
+        pub lookups: u32,
+    }
+
+    #[derive(Debug, Builder)]
+    #[builder(typestate)]
+    pub struct Counter {
+        pub name: String,
+        #[builder(skip = "42")]
+        pub count: u64,
+    }
+
+    pub(crate) fn resolver() -> Resolver {
+        Resolver::builder()
+            .upstream("1.1.1.1".to_owned())
+            .retries(2)
+            .build()
+            .unwrap()
+    }
+}
+
+use cache::{Counter, Resolver};
+
+fn main() {
+    let resolver = cache::resolver();
+    assert_eq!(resolver.upstream, "1.1.1.1");
+    assert_eq!(resolver.retries, 2);
+    assert!(resolver.cache.is_empty());
+    assert_eq!(resolver.generation, 1);
+    assert_eq!(resolver.lookups, 3);
+
+    let mut changed = Resolver {
+        lookups: 7,
+        generation: 5,
+        ..resolver
+    };
+    changed.cache.insert("a".to_owned(), "b".to_owned());
+    let rebuilt = changed.to_builder().build().unwrap();
+    assert_eq!(rebuilt.lookups, 7);
+    assert_eq!(rebuilt.generation, 1);
+    assert!(rebuilt.cache.is_empty());
+
+    let counter = Counter::builder().name("hits".to_owned()).build();
+    assert_eq!(counter.count, 42);
+}
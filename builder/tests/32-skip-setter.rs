@@ -0,0 +1,19 @@
+// A skipped field has no setter, and neither does one with setter(skip).
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Resolver {
+    upstream: String,
+    #[builder(skip)]
+    generation: u64,
+    #[builder(setter(skip))]
+    lookups: u32,
+}
+
+fn main() {
+    let mut builder = Resolver::builder();
+    builder.upstream("1.1.1.1".to_owned());
+    builder.generation(2);
+    builder.lookups(3);
+}
@@ -0,0 +1,49 @@
+// #[builder(try_setter)] adds a `try_` setter next to the field's setter. It
+// accepts anything with a TryInto conversion into the field's type and
+// returns the conversion error instead of setting the field when it fails,
+// so arguments of a wider or parsed type can be passed without converting
+// each of them by hand first.
+//
+// Like the other setters, the `try_` setter follows the builder's pattern:
+// with `pattern = "owned"` it takes and returns the builder by value, and in
+// a typestate builder it returns the builder with the field marked as set.
+
+use derive_builder::Builder;
+use std::net::Ipv4Addr;
+use std::num::TryFromIntError;
+
+#[derive(Debug, Builder)]
+pub struct Server {
+    #[builder(try_setter)]
+    port: u16,
+    #[builder(try_setter)]
+    workers: Option<u8>,
+    #[builder(try_setter, setter(into))]
+    address: Ipv4Addr,
+}
+
+#[derive(Debug, Builder)]
+#[builder(typestate)]
+pub struct Limits {
+    #[builder(try_setter)]
+    memory: u32,
+}
+
+fn main() -> Result<(), TryFromIntError> {
+    let mut builder = Server::builder();
+    builder.try_port(8080u64)?.try_workers(4i32)?;
+    builder.try_address([127, 0, 0, 1])?;
+    assert!(builder.try_port(-1i32).is_err());
+    assert!(builder.try_workers(1000u32).is_err());
+
+    let server = builder.build().unwrap();
+    assert_eq!(server.port, 8080);
+    assert_eq!(server.workers, Some(4));
+    assert_eq!(server.address, Ipv4Addr::LOCALHOST);
+
+    let limits = Limits::builder().try_memory(512u64)?.build();
+    assert_eq!(limits.memory, 512);
+    assert!(Limits::builder().try_memory(-1i64).is_err());
+
+    Ok(())
+}
@@ -0,0 +1,59 @@
+// Option fields are recognized however the standard Option is spelled:
+// `Option<T>`, `std::option::Option<T>` or `core::option::Option<T>`. Only
+// the last path segment of other paths is not enough, so a type alias for
+// an Option has to be marked #[builder(optional)], and an Option that must
+// be set explicitly, even if to None, can be marked #[builder(required)].
+//
+// The same goes for the collections whose `each` item type is inferred: they
+// may be written with a `std::`, `core::` or `alloc::` prefix.
+
+use derive_builder::Builder;
+
+type MaybePort = Option<u16>;
+
+mod shapes {
+    // Not std's Option, despite its name.
+    #[derive(Debug, PartialEq)]
+    pub struct Option(pub u8);
+}
+
+#[derive(Debug, Builder)]
+pub struct Server {
+    host: std::option::Option<String>,
+    timeout: core::option::Option<u64>,
+    #[builder(optional)]
+    port: MaybePort,
+    #[builder(required)]
+    proxy: Option<String>,
+    option: shapes::Option,
+    #[builder(each = "tag")]
+    tags: std::vec::Vec<String>,
+}
+
+fn main() {
+    let mut builder = Server::builder();
+    builder.option(shapes::Option(1));
+
+    let err = builder.build().unwrap_err();
+    assert_eq!(err.missing_fields(), ["proxy"]);
+
+    builder.proxy(None).host("localhost".to_owned()).tag("a".to_owned());
+    let server = builder.build().unwrap();
+    assert_eq!(server.host.as_deref(), Some("localhost"));
+    assert_eq!(server.timeout, None);
+    assert_eq!(server.port, None);
+    assert_eq!(server.proxy, None);
+    assert_eq!(server.option, shapes::Option(1));
+    assert_eq!(server.tags, ["a"]);
+
+    let server = Server::builder()
+        .option(shapes::Option(2))
+        .proxy(Some("proxy:3128".to_owned()))
+        .timeout(30)
+        .port(Some(8080))
+        .build()
+        .unwrap();
+    assert_eq!(server.timeout, Some(30));
+    assert_eq!(server.port, Some(8080));
+    assert_eq!(server.proxy.as_deref(), Some("proxy:3128"));
+}
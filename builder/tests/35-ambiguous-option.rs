@@ -0,0 +1,17 @@
+// A path that only ends in `Option<T>` may or may not be std's Option, so the
+// field has to say which it is with #[builder(optional)] or
+// #[builder(required)].
+
+use derive_builder::Builder;
+
+mod my {
+    pub type Option<T> = std::option::Option<T>;
+}
+
+#[derive(Builder)]
+pub struct Server {
+    host: String,
+    port: my::Option<u16>,
+}
+
+fn main() {}
@@ -0,0 +1,71 @@
+// A partially filled builder can be inspected and changed back:
+//
+//     get_<field>(&self)   the value set so far: Option<&T> for fields set
+//                          with a single value, or a reference to the
+//                          collection or sub-builder
+//     clear_<field>()      puts the field back into the state of a new builder
+//     is_complete(&self)   whether every required field, including those of
+//                          sub-builders, has been set
+//
+// The getters and clear_ methods share the visibility of the field's setters.
+
+use derive_builder::Builder;
+
+#[derive(Debug, Builder)]
+pub struct Tls {
+    cert: String,
+}
+
+#[derive(Debug, Builder)]
+pub struct Server {
+    host: String,
+    port: Option<u16>,
+    #[builder(default = "4")]
+    workers: usize,
+    #[builder(each = "alias")]
+    aliases: Vec<String>,
+    #[builder(sub_builder)]
+    tls: Tls,
+}
+
+#[derive(Debug, Builder)]
+#[builder(pattern = "owned")]
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+
+fn main() {
+    let mut builder = Server::builder();
+    assert_eq!(builder.get_host(), None);
+    assert_eq!(builder.get_port(), None);
+    assert_eq!(builder.get_workers(), None);
+    assert!(builder.get_aliases().is_empty());
+    assert!(!builder.is_complete());
+
+    builder
+        .host("localhost".to_owned())
+        .port(8080)
+        .alias("www".to_owned());
+    builder.tls().cert("cert.pem".to_owned());
+    assert_eq!(builder.get_host().map(String::as_str), Some("localhost"));
+    assert_eq!(builder.get_port(), Some(&8080));
+    assert_eq!(builder.get_aliases(), &["www"]);
+    assert_eq!(
+        builder.get_tls().get_cert().map(String::as_str),
+        Some("cert.pem")
+    );
+    assert!(builder.is_complete());
+
+    builder.clear_port().clear_aliases();
+    builder.tls().clear_cert();
+    assert_eq!(builder.get_port(), None);
+    assert!(builder.get_aliases().is_empty());
+    assert!(!builder.is_complete());
+    assert_eq!(builder.build().unwrap_err().missing_fields(), ["tls.cert"]);
+
+    let point = Point::builder().x(1).y(2).clear_y();
+    assert_eq!(point.get_x(), Some(&1));
+    assert!(!point.is_complete());
+    assert_eq!(point.y(3).build().unwrap().y, 3);
+}
@@ -0,0 +1,90 @@
+// #[builder(crate_root = "core")] makes the generated code refer only to
+// `core` and `alloc`, so that builders can be derived in no_std crates that
+// have an allocator. The crate has to declare `extern crate alloc;` itself.
+// The error type then implements core::error::Error.
+//
+// This test links std only for the runtime; it is not in scope under the name
+// `std`, so any path the derive emits through std would fail to resolve.
+
+#![no_std]
+
+extern crate alloc;
+extern crate std as _std;
+
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use derive_builder::Builder;
+
+#[derive(Debug, Builder)]
+#[builder(crate_root = "core", build_fn(validate = "Config::validate"))]
+pub struct Config {
+    name: String,
+    #[builder(each = "pin")]
+    pins: Vec<u8>,
+    baud: Option<u32>,
+    #[builder(default = "3", try_setter)]
+    retries: u8,
+    #[builder(sub_builder)]
+    radio: Radio,
+}
+
+impl Config {
+    fn validate(&self) -> Result<(), &'static str> {
+        if self.pins.is_empty() {
+            Err("no pins")
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[derive(Debug, Builder)]
+#[builder(crate_root = "core", to_builder)]
+#[derive(Clone)]
+pub struct Radio {
+    channel: u8,
+}
+
+#[derive(Debug, Builder)]
+#[builder(crate_root = "core", typestate)]
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+
+fn assert_error<E: core::error::Error>(_: &E) {}
+
+fn main() {
+    let mut builder = Config::builder();
+    let err = builder.build().unwrap_err();
+    assert_error(&err);
+    assert_eq!(err.missing_fields(), ["name", "radio.channel"]);
+    assert_eq!(err.to_string(), "Fields name, radio.channel are not set");
+
+    builder
+        .name("sensor".to_string())
+        .try_retries(5u32)
+        .unwrap();
+    builder.radio().channel(11);
+    let config = builder.pin(4).pin(5).build().unwrap();
+    assert_eq!(config.pins, vec![4, 5]);
+    assert_eq!(config.retries, 5);
+    assert_eq!(
+        config
+            .radio
+            .to_builder()
+            .channel(12)
+            .build()
+            .unwrap()
+            .channel,
+        12
+    );
+
+    let mut builder = Config::builder();
+    builder.name("sensor".to_string()).radio().channel(11);
+    assert_eq!(builder.build().unwrap_err().to_string(), "no pins");
+
+    let point = Point::builder().x(1).y(2).build();
+    assert_eq!((point.x, point.y), (1, 2));
+}
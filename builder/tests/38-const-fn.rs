@@ -0,0 +1,85 @@
+// #[builder(const_fn)] makes the constructor, the setters and build() `const
+// fn`, so that constants and statics can be built with the same API as
+// values at runtime.
+//
+// A const fn cannot call trait methods or run destructors. The setters
+// therefore take and return the builder by value, defaults have to be given
+// as const expressions with `default = "..."`, and options needing a trait
+// call such as `each` or `setter(into)` are not available. Fields may still
+// have types with destructors, like String or a type parameter: a value
+// replaced by calling a setter again is forgotten instead of dropped. Because
+// build() cannot report an error from a const context, it returns the value
+// directly and panics if a required field was not set, which fails the
+// compilation of a constant.
+
+use derive_builder::Builder;
+
+const NO_STATS: [u32; 4] = [0; 4];
+
+#[derive(Debug, PartialEq, Builder)]
+#[builder(const_fn)]
+pub struct Server {
+    host: &'static str,
+    #[builder(default = "80")]
+    port: u16,
+    tls: Option<bool>,
+    #[builder(skip = "NO_STATS")]
+    stats: [u32; 4],
+}
+
+#[derive(Debug, PartialEq, Builder)]
+#[builder(const_fn, build_fn(name = "finish"))]
+pub enum Pin {
+    Input { number: u8, pull_up: Option<bool> },
+    Output { number: u8 },
+}
+
+static SERVERS: [Server; 2] = [
+    Server::builder().host("a.example").port(8080).build(),
+    Server::builder().host("b.example").tls(true).build(),
+];
+
+const LED: Pin = Pin::output_builder().number(13).finish();
+
+#[derive(Debug, PartialEq, Builder)]
+#[builder(const_fn)]
+pub struct Labeled<T> {
+    value: T,
+    name: String,
+    #[builder(default = "String::new()")]
+    unit: String,
+    comment: Option<String>,
+}
+
+const ZERO: Labeled<u8> = Labeled::builder().value(0).name(String::new()).build();
+
+fn main() {
+    assert_eq!(SERVERS[0].host, "a.example");
+    assert_eq!(SERVERS[0].port, 8080);
+    assert_eq!(SERVERS[0].tls, None);
+    assert_eq!(SERVERS[1].port, 80);
+    assert_eq!(SERVERS[1].tls, Some(true));
+    assert_eq!(SERVERS[1].stats, [0; 4]);
+
+    assert_eq!(LED, Pin::Output { number: 13 });
+
+    assert_eq!(ZERO.unit, "");
+    let labeled = Labeled::builder()
+        .value(vec![1, 2])
+        .name("first".to_string())
+        .name("second".to_string())
+        .comment("set".to_string())
+        .build();
+    assert_eq!(labeled.value, [1, 2]);
+    assert_eq!(labeled.name, "second");
+    assert_eq!(labeled.comment.as_deref(), Some("set"));
+
+    let button = Pin::input_builder().number(2).pull_up(true).finish();
+    assert_eq!(
+        button,
+        Pin::Input {
+            number: 2,
+            pull_up: Some(true)
+        }
+    );
+}
@@ -0,0 +1,17 @@
+// A const builder's build() panics when a required field is not set, which
+// in a constant is reported as a compile error.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(const_fn)]
+pub struct Server {
+    host: &'static str,
+    port: u16,
+}
+
+const SERVER: Server = Server::builder().host("localhost").build();
+
+fn main() {
+    let _ = SERVER.port;
+}
@@ -0,0 +1,115 @@
+// The `///` comments of a field are copied onto its setters, so that the
+// builder documents the same things as the struct. The builder type, its
+// constructor and build function get generated documentation listing which
+// fields are required and what the others default to:
+//
+//     /// Builder for [`Server`].
+//     ///
+//     /// Required fields:
+//     ///
+//     /// - `host`
+//     ///
+//     /// Optional fields, and their value when not set:
+//     ///
+//     /// - `port`: `8080`
+//     /// - `aliases`: empty
+//     pub struct ServerBuilder { ... }
+//
+// A public struct with documented fields therefore gets a builder that passes
+// `missing_docs`, whichever kind of builder it is.
+
+#![deny(missing_docs)]
+//! Crate documentation.
+
+use derive_builder::Builder;
+
+/// A TLS configuration.
+#[derive(Debug, Builder)]
+#[builder(to_builder)]
+#[derive(Clone)]
+pub struct Tls {
+    /// Path to the certificate.
+    pub cert: String,
+}
+
+/// A server to listen on.
+#[derive(Debug, Builder)]
+#[builder(build_fn(validate = "Server::validate"), to_builder)]
+#[derive(Clone)]
+pub struct Server {
+    /// Host name or address.
+    pub host: String,
+    /// Port to listen on.
+    #[builder(default = "8080", try_setter)]
+    pub port: u16,
+    /// Other names of the host.
+    #[builder(each = "alias")]
+    pub aliases: Vec<String>,
+    /// Connection timeout in seconds.
+    pub timeout: Option<u64>,
+    /// TLS settings.
+    #[builder(sub_builder)]
+    pub tls: Tls,
+}
+
+impl Server {
+    fn validate(&self) -> Result<(), String> {
+        if self.port == 0 {
+            Err("port cannot be 0".to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// A point.
+#[derive(Debug, Builder)]
+#[builder(typestate)]
+pub struct Point {
+    /// Horizontal coordinate.
+    pub x: i32,
+    /// Vertical coordinate.
+    pub y: i32,
+    /// Label shown next to the point.
+    pub label: Option<String>,
+}
+
+/// A limit known at compile time.
+#[derive(Debug, Builder)]
+#[builder(const_fn, pattern = "owned")]
+pub struct Limit {
+    /// Maximum number of items.
+    pub max: usize,
+    /// Whether going over the limit is an error.
+    #[builder(default = "true")]
+    pub strict: bool,
+}
+
+/// A shape.
+#[derive(Debug, Builder)]
+pub enum Shape {
+    /// A circle.
+    Circle {
+        /// Radius of the circle.
+        radius: f64,
+    },
+}
+
+fn main() {
+    let mut builder = Server::builder();
+    builder
+        .host("localhost".to_string())
+        .alias("local".to_string());
+    builder.tls().cert("cert.pem".to_string());
+    let server = builder.build().unwrap();
+    assert_eq!(server.port, 8080);
+
+    let point = Point::builder().x(1).y(2).build();
+    assert_eq!((point.x, point.y), (1, 2));
+
+    const LIMIT: Limit = Limit::builder().max(10).build();
+    assert!(LIMIT.strict);
+
+    let shape = Shape::circle_builder().radius(1.0).build().unwrap();
+    assert!(matches!(shape, Shape::Circle { .. }));
+}
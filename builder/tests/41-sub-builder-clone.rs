@@ -0,0 +1,20 @@
+// With `pattern = "immutable"`, every setter clones the builder, so the
+// builder of a `sub_builder` field has to be Clone as well. A field builder
+// using the default mutable pattern is not, which is reported at the field.
+
+use derive_builder::Builder;
+
+#[derive(Debug, Builder)]
+pub struct Tls {
+    cert: String,
+}
+
+#[derive(Debug, Builder)]
+#[builder(pattern = "immutable")]
+pub struct Server {
+    host: String,
+    #[builder(sub_builder)]
+    tls: Tls,
+}
+
+fn main() {}
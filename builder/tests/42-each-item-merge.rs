@@ -0,0 +1,24 @@
+// A collection given an explicit `each(item = "...")` type is not known to be
+// iterable, so merge() cannot combine it without being told how. Leaving out
+// `merge = "..."` is an error pointing at the item type, rather than the
+// builder quietly going without merge(), which a parent builder using it as a
+// sub-builder needs.
+
+use derive_builder::Builder;
+
+#[derive(Default)]
+pub struct Tags(Vec<String>);
+
+impl Extend<String> for Tags {
+    fn extend<I: IntoIterator<Item = String>>(&mut self, iter: I) {
+        self.0.extend(iter);
+    }
+}
+
+#[derive(Builder)]
+pub struct Request {
+    #[builder(each(name = "tag", item = "String"))]
+    tags: Tags,
+}
+
+fn main() {}
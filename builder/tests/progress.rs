@@ -10,4 +10,38 @@ fn tests() {
     t.pass("tests/07-repeated-field.rs");
     t.compile_fail("tests/08-unrecognized-attribute.rs");
     t.pass("tests/09-redefined-prelude-types.rs");
+    t.pass("tests/10-generics.rs");
+    t.pass("tests/11-typestate.rs");
+    t.compile_fail("tests/12-typestate-missing-field.rs");
+    t.pass("tests/13-error-type.rs");
+    t.pass("tests/14-all-missing-fields.rs");
+    t.pass("tests/15-default-fields.rs");
+    t.pass("tests/16-setter-options.rs");
+    t.pass("tests/17-builder-pattern.rs");
+    t.pass("tests/18-each-collections.rs");
+    t.compile_fail("tests/19-each-unknown-item.rs");
+    t.pass("tests/20-collection-setters.rs");
+    t.pass("tests/21-validate.rs");
+    t.pass("tests/22-enum.rs");
+    t.pass("tests/23-tuple-struct.rs");
+    t.compile_fail("tests/24-multiple-errors.rs");
+    t.pass("tests/25-to-builder.rs");
+    t.pass("tests/26-sub-builder.rs");
+    t.pass("tests/27-merge.rs");
+    #[cfg(feature = "serde")]
+    t.pass("tests/28-serde.rs");
+    t.pass("tests/29-builder-names.rs");
+    t.compile_fail("tests/30-builder-names-invalid.rs");
+    t.pass("tests/31-skip.rs");
+    t.compile_fail("tests/32-skip-setter.rs");
+    t.pass("tests/33-try-setter.rs");
+    t.pass("tests/34-option-detection.rs");
+    t.compile_fail("tests/35-ambiguous-option.rs");
+    t.pass("tests/36-getters.rs");
+    t.pass("tests/37-no-std.rs");
+    t.pass("tests/38-const-fn.rs");
+    t.compile_fail("tests/39-const-fn-missing-field.rs");
+    t.pass("tests/40-docs.rs");
+    t.compile_fail("tests/41-sub-builder-clone.rs");
+    t.compile_fail("tests/42-each-item-merge.rs");
 }